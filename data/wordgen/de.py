@@ -0,0 +1,177 @@
+"""Write data/de/popular.txt, the German word list: fully inflected forms of
+the lemmas in the de_*.txt lists here, merged with de_base.txt.
+
+Run with Python 3 from any directory: python3 data/wordgen/de.py
+"""
+import os, re, sys
+D = os.path.dirname(os.path.abspath(__file__)) + "/"
+OUT = os.path.join(D, "..", "de", "popular.txt")
+def toks(f): return [t for t in open(D + f).read().split()]
+gen = set()
+irr = {}
+for line in open(D + 'de_irr.txt'):
+    w = line.split()
+    if w: irr[w[0]] = w; gen.update(w)
+
+INSEP = ('wieder', 'wider', 'hinter', 'unter', 'über', 'miss', 'emp', 'ent', 'zer', 'ver', 'be', 'ge', 'er')
+NOINSEP = {'beten', 'ernten', 'erben', 'geben', 'gelten', 'gehen', 'genießen'}
+def insep(v):
+    if v in NOINSEP: return False
+    return any(v.startswith(p) and len(v) - len(p) >= 5 for p in INSEP)
+
+def umlaut(w):
+    for a, b in (('au', 'äu'), ('a', 'ä'), ('o', 'ö'), ('u', 'ü')):
+        i = w.rfind(a)
+        if i >= 0 and not (a == 'u' and i > 0 and w[i - 1] in 'ae'):
+            # pick the rightmost full vowel
+            j = max(w.rfind(x) for x in 'aou')
+            if a == 'au' and i + 1 == j: return w[:i] + b + w[i + 2:]
+            if a != 'au' and i == j: return w[:i] + b + w[i + 1:]
+    return w
+
+def needs_e(stem):
+    return stem[-1] in 'td' or re.search('[^lrmnh][mn]$', stem) is not None
+
+def weak(v):
+    if v.endswith(('eln', 'ern')):
+        st = v[:-1]; one = (st[:-2] + 'le') if v.endswith('eln') else st + 'e'
+        e = ''
+    else:
+        st = v[:-2]; one = st + 'e'; e = 'e' if needs_e(st) else ''
+    two = st + e + ('t' if not e and st[-1] in 'sßzx' else 'st')
+    past = st + e + 'te'
+    ge = '' if v.endswith('ieren') or insep(v) else 'ge'
+    pp = ge + st + e + 't'
+    return [v, one, two, st + e + 't', past, past + 'st', past + 'n', past + 't', v + 'd'], pp
+
+def strong(parts):
+    v, third, past, pp, subj = parts
+    st = v[:-2] if not v.endswith(('eln', 'ern')) else v[:-1]
+    e = 'e' if needs_e(st) else ''
+    if third == st + 'et': two = st + 'est'
+    elif third.endswith('dt'): two = third[:-2] + 'dst'
+    elif third[:-1][-1:] in 'sßzx': two = third
+    elif st[-1] in 'td' : two = third + 'st'
+    else: two = third[:-1] + 'st'
+    f = [v, st + 'e', two, third, st + e + 't', v + 'd']
+    if past.endswith('te'):
+        f += [past, past + 'st', past + 'n', past + 't']
+    else:
+        pe = 'e' if past[-1] in 'tdsßz' else ''
+        f += [past, past + pe + 'st', past + 'en', past + pe + 't']
+    f += [subj, subj + 'st', subj + 'n', subj + 't']
+    return f, pp
+
+def derive(prefix, sep, forms, pp, v):
+    out = []
+    if sep:
+        out += [prefix + x for x in forms]
+        out.append(prefix + ('ge' + pp[2:] if False else pp))
+        out.append(prefix + 'zu' + v)
+    else:
+        out += [prefix + x for x in forms]
+        out.append(prefix + (pp[2:] if pp.startswith('ge') else pp))
+    return out
+
+pps = set()
+strongs = {}
+for line in open(D + 'de_strong.txt'):
+    if line.startswith('#') or not line.strip(): continue
+    w = line.split()
+    strongs[w[0]] = (w[:5], w[5:])
+for v, (parts, prefixes) in strongs.items():
+    forms, pp = strong(parts)
+    if v in irr: forms = []
+    else: gen.update(forms)
+    gen.add(pp); pps.add(pp)
+    base_forms = forms or irr[v][1:]
+    base_forms = [x for x in base_forms if x != pp]
+    for p in prefixes:
+        sep = p.endswith('|'); p = p.rstrip('|')
+        gen.update(derive(p, sep, [v] + [x for x in base_forms if x != v], pp, v))
+        if v in irr and not sep: pass
+
+def split_strong(v):
+    for p in sorted({p for _, (_, ps) in strongs.items() for p in ps} | set(INSEP), key=len, reverse=True):
+        q = p.rstrip('|')
+        if v.startswith(q) and v[len(q):] in strongs: return q, v[len(q):]
+    return None
+
+for t in toks('de_weak.txt'):
+    t, _, x = t.partition(':')
+    sep, _, rest = t.rpartition('|')
+    if x == 'x':
+        if rest in strongs:
+            base = rest
+            pre = ''
+        else:
+            s = split_strong(rest)
+            if not s: print('unknown', t, file=sys.stderr); continue
+            pre, base = s
+        parts, _ = strongs[base]
+        forms, pp = strong(parts)
+        if base in irr: forms = [f for f in irr[base][1:] if not f.startswith('ge')]
+        if pre: forms, pp = [pre + f for f in forms], pre + (pp[2:] if pp.startswith('ge') else pp)
+        forms = [pre + base] + forms
+    else:
+        forms, pp = weak(rest)
+    if sep:
+        gen.update(sep + f for f in forms)
+        gen.add(sep + pp); gen.add(sep + 'zu' + rest); pps.add(sep + pp)
+    else:
+        gen.update(forms); gen.add(pp); pps.add(pp)
+
+for t in toks('de_nouns.txt'):
+    w, g, code = t.split('/')
+    stem = w
+    if code == '-': pl = w
+    elif code == '¨': pl = umlaut(w)
+    elif code.startswith('¨'): pl = umlaut(w) + code[1:]
+    elif code == 'n' and w.endswith('in'): pl = w + 'nen'
+    elif code == 'nen': pl = w + 'nen'
+    elif code == 'se': pl = w + 'se'
+    elif code.startswith('='): pl = code[1:]
+    else: pl = w + code
+    if w in ('firma', 'museum'): pl = {'firma': 'firmen', 'museum': 'museen'}[w]
+    gen.update([w, pl])
+    if not pl.endswith(('n', 's')): gen.add(pl + 'n')
+    if g in 'mn':
+        weakm = g == 'm' and code in ('n', 'en') and not w.endswith(('or',))
+        if weakm: gen.add(w + ('n' if w.endswith('e') else 'en'))
+        elif w.endswith('nis'): gen.add(w + 'ses')
+        elif w.endswith(('s', 'ß', 'z', 'sch', 'x')): gen.add(w + 'es')
+        else: gen.add(w + 's')
+
+ENDS = ('', 'e', 'en', 'er', 'es', 'em')
+SPECIAL = {'gut': ('besser', 'best'), 'viel': ('mehr', 'meist'), 'hoch': ('höher', 'höchst'), 'nah': ('näher', 'nächst'), 'groß': ('größer', 'größt'), 'gern': ('lieber', 'liebst')}
+NOCOMP = set('tot rosa lila deutsch englisch französisch spanisch italienisch russisch chinesisch japanisch europäisch amerikanisch letzt nächst erst ander einzig mehr meist fast später bald halb ganz täglich wöchentlich monatlich jährlich verheiratet ledig geschieden geschlossen kostenlos sofort endlich plötzlich'.split())
+NODECL = {'rosa', 'lila', 'super', 'prima', 'fast', 'später', 'bald', 'sofort', 'endlich', 'mehr', 'viel', 'wenig', 'fit'}
+for t in toks('de_adj.txt'):
+    um = t.endswith('¨'); w = t.rstrip('¨')
+    gen.add(w)
+    if w in NODECL: continue
+    base = w[:-2] + w[-1] if w.endswith(('el', 'er')) and w not in ('super',) and len(w) > 4 else w
+    if w == 'hoch': base = 'hoh'
+    if w.endswith('e'): base = w[:-1]
+    gen.update(base + e for e in ENDS[1:])
+    if w in NOCOMP: continue
+    if w in SPECIAL: comp, sup = SPECIAL[w]
+    else:
+        cb = umlaut(w) if um else w
+        if w.endswith('e'):
+            comp, sup = cb + 'r', cb[:-1] + 'est'
+        else:
+            comp = (base if base != w else cb) + 'er'
+            sup = cb + ('est' if re.search('([dtsßzx]|sch)$', w) and not w.endswith('isch') else 'st')
+    gen.update(comp + e for e in ENDS)
+    gen.update(sup + e for e in ENDS[1:])
+    gen.add('am'); gen.add(sup + 'en')
+
+for pp in pps - {'gewesen', 'geworden', 'gekonnt', 'gedurft', 'gemusst', 'gemocht', 'gewollt', 'gesollt'}:
+    gen.update(pp + e for e in ENDS[1:])
+gen.update(toks('de_misc.txt'))
+gen = {x.lower() for x in gen}
+bad = sorted(x for x in gen if not re.fullmatch('[a-zäöüß]+', x))
+if bad: print('bad', bad[:20], file=sys.stderr)
+out = gen | set(open(D + 'de_base.txt').read().split())
+open(OUT, 'w').write('\n'.join(sorted(out)) + '\n')
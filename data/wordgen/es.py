@@ -0,0 +1,147 @@
+"""Write data/es/popular.txt, the Spanish word list: fully inflected forms of
+the lemmas in the es_*.txt lists here, merged with es_base.txt.
+
+Run with Python 3 from any directory: python3 data/wordgen/es.py
+"""
+import os, re, sys
+D = os.path.dirname(os.path.abspath(__file__)) + "/"
+OUT = os.path.join(D, "..", "es", "popular.txt")
+def toks(f): return open(D + f).read().split()
+
+out = set()
+irregular = set()
+for line in open(D + 'es_irr.txt'):
+    w = line.split()
+    if not w: continue
+    irregular.add(w[0]); out.update(w)
+
+STRIP = str.maketrans('áéíóú', 'aeiou')
+ACC = dict(zip('aeiou', 'áéíóú'))
+
+def soften(stem, v):
+    # spelling before e (-ar) or before a/o (-er/-ir)
+    if v == 'e':
+        if stem.endswith('gu'): return stem[:-2] + 'gü'
+        if stem.endswith('c'): return stem[:-1] + 'qu'
+        if stem.endswith('g'): return stem[:-1] + 'gu'
+        if stem.endswith('z'): return stem[:-1] + 'c'
+    else:
+        if stem.endswith('gu'): return stem[:-2] + 'g'
+        if stem.endswith('g'): return stem[:-1] + 'j'
+        if stem.endswith('c') and len(stem) > 1 and stem[-2] not in 'aeiou': return stem[:-1] + 'z'
+    return stem
+
+def last(stem, v, rep):
+    i = stem.rfind(v)
+    if i < 0: return stem
+    # keep u of gu/qu
+    return stem[:i] + rep + stem[i + 1:]
+
+def strong(stem, tag):
+    if tag in ('ie', 'ie-i'): return last(stem, 'e', 'ie')
+    if tag in ('ue', 'ue-u'):
+        if stem.startswith('o') and stem.rfind('o') == 0: return 'hue' + stem[1:]
+        return last(stem, 'o', 'ue')
+    if tag == 'i': return last(stem, 'e', 'i')
+    if tag in ('í', 'ú'):
+        v = tag.translate(STRIP); return last(stem, v, tag)
+    return stem
+
+def weak(stem, tag):
+    if tag in ('i', 'ie-i'): return last(stem, 'e', 'i')
+    if tag == 'ue-u': return last(stem, 'o', 'u')
+    return stem
+
+def participle(pp):
+    base = pp[:-1]
+    return [pp, base + 'a', base + 'os', base + 'as']
+
+def ar(verb, tag):
+    st = verb[:-2]; s = strong(st, tag); se = soften(st, 'e'); sse = soften(s, 'e')
+    f = [verb, s + 'o', s + 'as', s + 'a', st + 'amos', s + 'an',
+         se + 'é', st + 'aste', st + 'ó', st + 'aron',
+         st + 'aba', st + 'abas', st + 'ábamos', st + 'aban',
+         sse + 'e', sse + 'es', se + 'emos', sse + 'en',
+         st + 'ara', st + 'aras', st + 'áramos', st + 'aran', st + 'ando']
+    f += [verb + e for e in ('é', 'ás', 'á', 'emos', 'án', 'ía', 'ías', 'íamos', 'ían')]
+    f += participle(st + 'ado')
+    return f
+
+def erir(verb, tag, pp):
+    st = verb[:-2]; th = verb[-2]; s = strong(st, tag); w = weak(st, tag)
+    if tag == 'y':
+        y = st + 'y'
+        f = [verb, y + 'o', y + 'es', y + 'e', st + 'imos', y + 'en',
+             st + 'í', st + 'iste', st + 'yó', st + 'yeron',
+             y + 'a', y + 'as', y + 'amos', y + 'an',
+             st + 'yera', st + 'yeras', st + 'yéramos', st + 'yeran', st + 'yendo']
+    else:
+        if tag == 'zc':
+            one = st[:-1] + 'zc'; sub = one
+        else:
+            one = soften(s, 'o'); sub = one
+        subw = soften(w if th == 'i' else st, 'a')
+        f = [verb, one + 'o', s + 'es', s + 'e', st + th + 'mos' if th == 'i' else st + 'emos', s + 'en',
+             st + 'í', st + 'iste', w + 'ió', w + 'ieron', st + 'imos' if th == 'e' else None,
+             sub + 'a', sub + 'as', subw + 'amos', sub + 'an',
+             w + 'iera', w + 'ieras', w + 'iéramos', w + 'ieran', w + 'iendo']
+        f = [x for x in f if x]
+    f += [st + e for e in ('ía', 'ías', 'íamos', 'ían')]
+    f += [verb + e for e in ('é', 'ás', 'á', 'emos', 'án', 'ía', 'ías', 'íamos', 'ían')]
+    f += participle(pp or st + 'ido')
+    return f
+
+gen = set()
+def add(words):
+    for x in words:
+        if x.endswith('iió') or 'ii' in x: x = x.replace('iió', 'ió')
+        gen.add(x)
+
+for t in toks('es_ar.txt'):
+    v, _, tag = t.partition(':')
+    if v in irregular: continue
+    if v.endswith('ar'): add(ar(v, tag))
+for t in toks('es_erir.txt') + 'tejer atrever pretender revolver:ue=revuelto'.split():
+    t, _, pp = t.partition('=')
+    v, _, tag = t.partition(':')
+    if v in irregular: continue
+    add(erir(v, tag, pp))
+
+def noun_plural(w):
+    if w[-1] in 'aeiouáéó': return w + 's'
+    if w[-1] in 'íú': return w + 's'
+    if w.endswith('z'): return w[:-1] + 'ces'
+    m = re.search(r'([áéíóú])([nsl]?)$', w)
+    if w.endswith('s'):
+        if m: return w[:-2] + m.group(1).translate(STRIP) + 'ses'
+        if len(re.findall('[aeiouáéíóú]+', w)) == 1: return w + 'es'
+        return w
+    if m and m.group(2): return w[:-2] + m.group(1).translate(STRIP) + m.group(2) + 'es'
+    return w + 'es'
+EXTRA = {'examen': 'exámenes', 'joven': 'jóvenes', 'origen': 'orígenes', 'imagen': 'imágenes', 'orden': 'órdenes', 'carácter': 'caracteres'}
+for w in toks('es_nouns.txt'):
+    gen.add(w); gen.add(EXTRA.get(w) or noun_plural(w))
+
+NOMENTE = set('primero segundo tercero cuarto quinto sexto séptimo octavo noveno décimo blanco negro rojo verde azul amarillo gris marrón morado rosado dorado plateado europeo español inglés francés alemán italiano americano mexicano argentino chino japonés ruso árabe latino católico cristiano judío musulmán vivo muerto gordo flaco feo lindo guapo bonito enfermo sucio mojado húmedo caro barato bajo alto mayor menor mejor peor animal vegetal redondo cuadrado mismo propio solo junto próximo último diario semanal mensual anual bueno malo grande pequeño ancho viejo joven tarde temprano'.split())
+NOFEM = set('mayor menor mejor peor superior inferior interior exterior anterior posterior'.split())
+for w in toks('es_adj.txt'):
+    if w.endswith('o'):
+        fem = w[:-1] + 'a'; forms = [w, fem, w + 's', fem + 's']
+    elif w.endswith('z'):
+        fem = w; forms = [w, w[:-1] + 'ces']
+    elif re.search('[áéó]n$|és$|or$', w) and w not in NOFEM:
+        base = w[:-2] + w[-2].translate(STRIP) + w[-1]
+        fem = base + 'a'; forms = [w, fem, base + 'es', fem + 's']
+    elif w[-1] in 'aeí':
+        fem = w; forms = [w, w + 's']
+    else:
+        fem = w; forms = [w, noun_plural(w)]
+    gen.update(forms)
+    if w not in NOMENTE: gen.add(fem + 'mente')
+
+gen.update(toks('es_misc.txt'))
+bad = sorted(x for x in gen if not re.fullmatch('[a-zñáéíóúü]+', x))
+if bad: print('bad', bad[:20], file=sys.stderr)
+out |= gen
+out |= set(open(D + 'es_base.txt').read().split())
+open(OUT, 'w').write('\n'.join(sorted(out)) + '\n')
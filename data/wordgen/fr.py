@@ -0,0 +1,259 @@
+"""Write data/fr/popular.txt, the French word list: fully inflected forms of
+the lemmas in the fr_*.txt lists here, merged with fr_base.txt.
+
+Run with Python 3 from any directory: python3 data/wordgen/fr.py
+"""
+import os, re, sys
+D = os.path.dirname(os.path.abspath(__file__)) + "/"
+OUT = os.path.join(D, "..", "fr", "popular.txt")
+words = set()
+def add(*ws):
+    for w in ws:
+        for x in w.split():
+            words.add(x)
+
+VOWELS = "aeiouyéèêëàâîïôûù"
+
+# ---------- irregular verb families: base forms, members share the ending
+FAMILIES = {
+"être": ("être suis es est sommes êtes sont étais était étions étiez étaient serai seras sera serons serez seront serais serait serions seriez seraient fus fut fûmes furent sois soit soyons soyez soient fût été étant", ["être"]),
+"avoir": ("avoir ai as a avons avez ont avais avait avions aviez avaient aurai auras aura aurons aurez auront aurais aurait aurions auriez auraient eus eut eûmes eurent aie aies ait ayons ayez aient eu eue eues ayant eût", ["avoir"]),
+"aller": ("aller vais vas va allons allez vont allais allait allions alliez allaient irai iras ira irons irez iront irais irait irions iriez iraient allai alla allèrent aille ailles aillent allé allée allés allées allant", ["aller"]),
+"faire": ("faire fais fait faisons faites font faisais faisait faisions faisiez faisaient ferai feras fera ferons ferez feront ferais ferait ferions feriez feraient fis fit fîmes firent fasse fasses fassions fassiez fassent faite faits faisant", ["faire", "défaire", "refaire", "satisfaire"]),
+"dire": ("dire dis dit disons disent disais disait disions disiez disaient dirai diras dira dirons direz diront dirais dirait dirions diriez diraient dirent dise dises dite dits disant", ["dire", "redire", "interdire", "contredire", "prédire"]),
+"pouvoir": ("pouvoir peux peut pouvons pouvez peuvent pouvais pouvait pouvions pouviez pouvaient pourrai pourras pourra pourrons pourrez pourront pourrais pourrait pourrions pourriez pourraient pus put purent puisse puisses puissions puissiez puissent pu pouvant", ["pouvoir"]),
+"vouloir": ("vouloir veux veut voulons voulez veulent voulais voulait voulions vouliez voulaient voudrai voudras voudra voudrons voudrez voudront voudrais voudrait voudrions voudriez voudraient voulus voulut voulurent veuille veuilles veuillent voulu voulue voulant", ["vouloir"]),
+"savoir": ("savoir sais sait savons savez savent savais savait savions saviez savaient saurai sauras saura saurons saurez sauront saurais saurait saurions sauriez sauraient sus sut surent sache saches sachions sachiez sachent su sue sachant", ["savoir"]),
+"voir": ("voir vois voit voyons voyez voient voyais voyait voyions voyiez voyaient verrai verras verra verrons verrez verront verrais verrait verrions verriez verraient vis vit virent voie voies vu vue vus vues voyant", ["voir", "revoir", "entrevoir"]),
+"venir": ("venir viens vient venons venez viennent venais venait venions veniez venaient viendrai viendras viendra viendrons viendrez viendront viendrais viendrait viendrions viendriez viendraient vins vint vinrent vienne viennes venu venue venus venues venant", ["venir", "devenir", "revenir", "prévenir", "souvenir", "convenir", "parvenir", "intervenir", "survenir", "provenir"]),
+"tenir": ("tenir tiens tient tenons tenez tiennent tenais tenait tenions teniez tenaient tiendrai tiendras tiendra tiendrons tiendrez tiendront tiendrais tiendrait tiendrions tiendriez tiendraient tins tint tinrent tienne tiennes tenu tenue tenus tenues tenant", ["tenir", "obtenir", "retenir", "contenir", "maintenir", "appartenir", "soutenir", "détenir", "entretenir", "abstenir"]),
+"prendre": ("prendre prends prend prenons prenez prennent prenais prenait prenions preniez prenaient prendrai prendras prendra prendrons prendrez prendront prendrais prendrait prendrions prendriez prendraient pris prit prirent prenne prennes prise prises prenant", ["prendre", "apprendre", "comprendre", "surprendre", "reprendre", "entreprendre"]),
+"mettre": ("mettre mets met mettons mettez mettent mettais mettait mettions mettiez mettaient mettrai mettras mettra mettrons mettrez mettront mettrais mettrait mettrions mettriez mettraient mis mit mirent mette mettes mise mises mettant", ["mettre", "admettre", "permettre", "promettre", "remettre", "soumettre", "commettre", "transmettre", "émettre", "compromettre", "omettre"]),
+"devoir": ("devoir dois doit devons devez doivent devais devait devions deviez devaient devrai devras devra devrons devrez devront devrais devrait devrions devriez devraient dus dut durent doive doives dû due dues devant", ["devoir"]),
+"crire": ("crire cris crit crivons crivez crivent crivais crivait crivions criviez crivaient crirai criras crira crirons crirez criront crirais crirait crivis crivit crivirent crive crives crite crits crites crivant", ["écrire", "décrire", "inscrire", "prescrire", "souscrire", "transcrire"]),
+"lire": ("lire lis lit lisons lisez lisent lisais lisait lisions lisiez lisaient lirai liras lira lirons lirez liront lirais lirait lus lut lurent lise lises lu lue lues lisant", ["lire", "relire", "élire"]),
+"boire": ("boire bois boit buvons buvez boivent buvais buvait buvions buviez buvaient boirai boiras boira boirons boirez boiront boirais boirait bus but burent boive boives bu bue bues buvant", ["boire"]),
+"croire": ("croire crois croit croyons croyez croient croyais croyait croyions croyiez croyaient croirai croiras croira croirons croirez croiront croirais croirait crus crut crurent croie croies cru crue crues croyant", ["croire"]),
+"vivre": ("vivre vis vit vivons vivez vivent vivais vivait vivions viviez vivaient vivrai vivras vivra vivrons vivrez vivront vivrais vivrait vécus vécut vécurent vive vives vécu vécue vécues vivant", ["vivre", "revivre", "survivre"]),
+"suivre": ("suivre suis suit suivons suivez suivent suivais suivait suivions suiviez suivaient suivrai suivras suivra suivrons suivrez suivront suivrais suivrait suivis suivit suivirent suive suives suivi suivie suivies suivant", ["suivre", "poursuivre"]),
+"courir": ("courir cours court courons courez courent courais courait courions couriez couraient courrai courras courra courrons courrez courront courrais courrait courus courut coururent coure coures couru courue courues courant", ["courir", "parcourir", "accourir", "secourir", "recourir", "concourir"]),
+"mourir": ("mourir meurs meurt mourons mourez meurent mourais mourait mourrai mourra mourront mourrait mourut moururent meure mort morte morts mortes mourant", ["mourir"]),
+"naître": ("naître nais naît naissons naissez naissent naissais naissait naîtrai naîtra naquit naquirent né née nés nées naissant", ["naître"]),
+"plaire": ("plaire plais plaît plaisons plaisez plaisent plaisais plaisait plairai plaira plairait plut plurent plaise plu plaisant", ["plaire", "déplaire"]),
+"rire": ("rire ris rit rions riez rient riais riait riions riiez riaient rirai riras rira rirons rirez riront rirais rirait rirent rie ries ri riant", ["rire", "sourire"]),
+"battre": ("battre bats bat battons battez battent battais battait battions battiez battaient battrai battras battra battrons battrez battront battrais battrait battis battit battirent batte battes battu battue battus battues battant", ["battre", "combattre", "abattre", "débattre"]),
+"valoir": ("valoir vaux vaut valons valez valent valais valait vaudrai vaudra vaudrait valut valurent vaille valu valant", ["valoir"]),
+"falloir": ("falloir faut fallait faudra faudrait fallut faille fallu", ["falloir"]),
+"pleuvoir": ("pleuvoir pleut pleuvait pleuvra pleuvrait pleuve plu pleuvant", ["pleuvoir"]),
+"asseoir": ("asseoir assieds assied asseyons asseyez asseyent asseyais asseyait assiérai assiéra assis assise assises asseyant", ["asseoir"]),
+"clure": ("clure clus clut cluons cluez cluent cluais cluait clurai clura clurait clurent clue cluant", ["conclure", "exclure", "inclure"]),
+"vaincre": ("vaincre vaincs vainc vainquons vainquez vainquent vainquais vainquait vaincrai vaincra vainquit vainquirent vainque vaincu vaincue vaincus vaincues vainquant", ["vaincre", "convaincre"]),
+"suffire": ("suffire suffis suffit suffisons suffisez suffisent suffisait suffira suffirait suffirent suffise suffi suffisant", ["suffire"]),
+"fuir": ("fuir fuis fuit fuyons fuyez fuient fuyais fuyait fuirai fuira fuirait fuirent fuie fui fuyant", ["fuir", "enfuir"]),
+"quérir": ("quérir quiers quiert quérons quérez quièrent quérais quérait querrai querra querrait quis quit quirent quière quise quises quérant", ["conquérir", "acquérir"]),
+"mouvoir": ("mouvoir meus meut mouvons mouvez meuvent mouvait mouvra mu mue mus mues mouvant", ["émouvoir"]),
+"rompre": ("rompre romps rompt rompons rompez rompent rompais rompait romprai rompra romprait rompis rompit rompirent rompe rompu rompue rompus rompues rompant", ["rompre", "interrompre", "corrompre"]),
+"résoudre": ("résoudre résous résout résolvons résolvez résolvent résolvait résoudrai résoudra résolut résolu résolue résolus résolvant", ["résoudre"]),
+"coudre": ("coudre couds coud cousons cousez cousent cousait coudra cousu cousue cousant", ["coudre"]),
+"taire": ("taire tais tait taisons taisez taisent taisait taira tut tue taisant", ["taire"]),
+"haïr": ("haïr hais hait haïssons haïssez haïssent haïssait haïra haï haïe haïs haïssant", ["haïr"]),
+"envoyer": ("envoyer envoie envoies envoyons envoyez envoient envoyais envoyait envoyions envoyiez envoyaient enverrai enverras enverra enverrons enverrez enverront enverrais enverrait enverrions enverriez enverraient envoyai envoya envoyèrent envoyé envoyée envoyés envoyées envoyant", ["envoyer", "renvoyer"]),
+"cueillir": ("cueillir cueille cueilles cueillons cueillez cueillent cueillais cueillait cueillions cueilliez cueillaient cueillerai cueilleras cueillera cueillerons cueillerez cueilleront cueillerais cueillerait cueillis cueillit cueillirent cueilli cueillie cueillies cueillant", ["cueillir", "accueillir", "recueillir"]),
+}
+EXTRA = {"interdire": "interdisez", "contredire": "contredisez", "prédire": "prédisez",
+         "dire": "dites", "redire": "redites", "faire": "faites", "défaire": "défaites", "refaire": "refaites", "satisfaire": "satisfaites",
+         "inclure": "inclus incluse incluses", "conclure": "conclu conclue conclus conclues", "exclure": "exclu exclue exclus exclues"}
+irregular = set()
+for base, (forms, members) in FAMILIES.items():
+    for member in members:
+        assert member.endswith(base), member
+        prefix = member[: len(member) - len(base)]
+        add(*(prefix + f for f in forms.split()))
+        irregular.add(member)
+for verb, forms in EXTRA.items():
+    add(forms)
+
+def partir(inf, short):
+    stem = inf[:-2]
+    add(inf, short + "s", short + "t", stem + "ons", stem + "ez", stem + "ent",
+        *(stem + e for e in "e es ais ait ions iez aient".split()),
+        *(inf + e for e in "ai as a ons ez ont ais ait ions iez aient".split()),
+        stem + "is", stem + "it", stem + "irent", stem + "i", stem + "ie", stem + "ies", stem + "ant")
+for inf, short in [("partir", "par"), ("repartir", "repar"), ("sortir", "sor"), ("ressortir", "ressor"), ("dormir", "dor"), ("endormir", "endor"),
+                   ("sentir", "sen"), ("ressentir", "ressen"), ("consentir", "consen"), ("pressentir", "pressen"), ("servir", "ser"),
+                   ("desservir", "desser"), ("mentir", "men"), ("démentir", "démen")]:
+    partir(inf, short)
+    irregular.add(inf)
+
+def ouvrir(inf, pp):
+    stem = inf[:-2]
+    add(inf, *(stem + e for e in "e es ons ez ent ais ait ions iez aient is it irent ant".split()),
+        *(inf + e for e in "ai as a ons ez ont ais ait ions iez aient".split()),
+        pp, pp + "e", pp + "s", pp + "es")
+for inf in ["ouvrir", "rouvrir", "couvrir", "recouvrir", "découvrir", "offrir", "souffrir"]:
+    ouvrir(inf, inf[:-3] + "ert")
+    irregular.add(inf)
+
+def dre(inf):
+    s = inf[:-2]
+    add(inf, s + "s", s, *(s + e for e in "ons ez ent ais ait ions iez aient is it irent e es u ue us ues ant".split()),
+        *(s + "r" + e for e in "ai as a ons ez ont ais ait ions iez aient".split()))
+for inf in "rendre vendre attendre entendre répondre perdre descendre défendre dépendre tendre fondre mordre tordre confondre correspondre étendre prétendre suspendre répandre pondre tondre fendre pendre détendre revendre redescendre".split():
+    dre(inf)
+    irregular.add(inf)
+
+def indre(inf):
+    x = inf[:-4]
+    add(inf, x + "ns", x + "nt", *(x + "gn" + e for e in "ons ez ent ais ait ions iez aient is it irent e es ant".split()),
+        *(x + "ndr" + e for e in "ai as a ons ez ont ais ait ions iez aient".split()),
+        x + "nte", x + "nts", x + "ntes")
+for inf in "peindre éteindre craindre plaindre joindre atteindre rejoindre teindre feindre restreindre contraindre enfreindre".split():
+    indre(inf)
+    irregular.add(inf)
+
+def uire(inf):
+    x = inf[:-2]
+    add(inf, x + "s", x + "t", *(x + "s" + e for e in "ons ez ent ais ait ions iez aient is it irent e es ant".split()),
+        *(x + "r" + e for e in "ai as a ons ez ont ais ait ions iez aient".split()),
+        x + "te", x + "ts", x + "tes")
+for inf in "conduire construire produire détruire réduire traduire séduire introduire déduire instruire cuire reproduire reconstruire".split():
+    uire(inf)
+    irregular.add(inf)
+
+def aitre(inf):
+    x = inf[:-5]
+    add(inf, x + "ais", x + "aît", *(x + "aiss" + e for e in "ons ez ent ais ait ions iez aient e es ant".split()),
+        *(x + "aîtr" + e for e in "ai as a ons ez ont ais ait ions iez aient".split()),
+        *(x + e for e in "us ut urent u ue ues".split()))
+for inf in "connaître reconnaître paraître apparaître disparaître comparaître".split():
+    aitre(inf)
+    irregular.add(inf)
+
+def cevoir(inf):
+    x = inf[:-6]
+    add(inf, *(x + e for e in "çois çoit cevons cevez çoivent cevais cevait cevions ceviez cevaient çus çut çurent çoive çoives çu çue çues cevant".split()),
+        *(x + "cevr" + e for e in "ai as a ons ez ont ais ait ions iez aient".split()))
+for inf in "recevoir apercevoir concevoir décevoir percevoir".split():
+    cevoir(inf)
+    irregular.add(inf)
+
+# ---------- second group
+FINIR = """finir choisir agir aboutir accomplir affaiblir affermir agrandir alourdir applaudir aplatir approfondir avertir bannir bâtir blanchir bondir chérir compatir
+convertir définir démolir désobéir embellir enrichir envahir établir fléchir fournir franchir garantir gémir grandir grossir guérir investir jaillir languir mûrir munir
+noircir nourrir obéir pâlir polir punir raccourcir rafraîchir rajeunir ralentir ravir réagir rebondir réfléchir réjouir remplir rétablir réunir réussir rôtir rougir rugir
+saisir subir surgir tiédir trahir unir vernir vieillir maigrir salir anéantir assouplir atterrir durcir élargir éblouir épanouir frémir fleurir gravir moisir mincir refroidir
+retentir rétrécir ternir vomir adoucir affranchir amortir arrondir assombrir bénir brandir croupir éclaircir endurcir faiblir jaunir jouir meurtrir obscurcir périr pourrir
+raidir verdir fournir divertir""".split()
+for inf in FINIR:
+    s = inf[:-2]
+    add(inf, *(s + e for e in "is it issons issez issent issais issait issions issiez issaient irai iras ira irons irez iront irais irait irions iriez iraient irent isse isses i ie ies issant".split()))
+    irregular.add(inf)
+
+# ---------- first group
+DOUBLE = set("appeler rappeler jeter rejeter projeter épeler renouveler atteler niveler étiqueter ficeler feuilleter ensorceler grommeler cacheter empaqueter étinceler ruisseler chanceler breveter pelleter morceler museler amonceler".split())
+def er_forms(inf):
+    s = inf[:-2]
+    soft = s
+    if s.endswith("c"):
+        soft = s[:-1] + "ç"
+    elif s.endswith("g"):
+        soft = s + "e"
+    # stem before a mute e
+    mute, fut = s, s
+    if s.endswith("y") and not s.endswith("ey"):
+        mute = fut = s[:-1] + "i"
+    elif inf in DOUBLE:
+        mute = fut = s + s[-1]
+    else:
+        core = s[:-1] if s[-2:] in ("gu", "qu") else s
+        m = re.search(r"([" + VOWELS + r"])([^" + VOWELS + r"]+)$", core)
+        if m and not re.search(r"[" + VOWELS + r"]$", core):
+            v, cons = m.group(1), m.group(2)
+            start = m.start(1)
+            if v == "e" and ((len(cons) == 1 and cons != "x") or cons in ("vr", "br")) and not (start > 0 and core[start - 1] in "aeiouyé"):
+                mute = fut = s[:start] + "è" + s[start + 1:]
+            elif v == "é":
+                mute = s[:start] + "è" + s[start + 1:]
+    out = [inf,
+           mute + "e", mute + "es", mute + "ent",
+           soft + "ons", s + "ez",
+           soft + "ais", soft + "ait", s + "ions", s + "iez", soft + "aient",
+           *(fut + "er" + e for e in "ai as a ons ez ont ais ait aient".split()),
+           s + "é", s + "ée", s + "és", s + "ées", soft + "ant"]
+    add(*out)
+
+for inf in open(D + "fr_er.txt").read().split():
+    if inf in irregular:
+        continue
+    if inf.endswith("er") and inf not in ("aller",):
+        er_forms(inf)
+
+# ---------- nouns
+NOUN_PLURAL = {"œil": "yeux", "ciel": "cieux", "travail": "travaux", "bijou": "bijoux", "caillou": "cailloux", "chou": "choux", "genou": "genoux",
+               "hibou": "hiboux", "festival": "festivals", "madame": "mesdames", "monsieur": "messieurs", "pneu": "pneus", "bleu": "bleus",
+               "détail": "détails", "gens": "gens", "lunettes": "lunettes", "euro": "euros", "bal": "bals", "carnaval": "carnavals"}
+for n in open(D + "fr_nouns.txt").read().split():
+    if "-" in n:
+        continue
+    if n in NOUN_PLURAL:
+        p = NOUN_PLURAL[n]
+    elif n[-1] in "sxz":
+        p = n
+    elif n.endswith("al"):
+        p = n[:-2] + "aux"
+    elif n.endswith(("eau", "au", "eu")):
+        p = n + "x"
+    else:
+        p = n + "s"
+    add(n, p)
+
+# ---------- adjectives
+ADJ = {
+ "beau": "bel belle beaux belles", "nouveau": "nouvel nouvelle nouveaux nouvelles", "vieux": "vieil vieille vieilles",
+ "fou": "fol folle fous folles", "mou": "mol molle mous molles", "blanc": "blanche blancs blanches", "franc": "franche francs franches",
+ "sec": "sèche secs sèches", "frais": "fraîche fraîches", "doux": "douce douces", "faux": "fausse fausses", "roux": "rousse rousses",
+ "jaloux": "jalouse jalouses", "long": "longue longs longues", "gentil": "gentille gentils gentilles", "pareil": "pareille pareils pareilles",
+ "gros": "grosse grosses", "bas": "basse basses", "épais": "épaisse épaisses", "gras": "grasse grasses", "favori": "favorite favoris favorites",
+ "aigu": "aiguë aigus aiguës", "public": "publique publics publiques", "grec": "grecque grecs grecques", "malin": "maligne malins malignes",
+ "bref": "brève brefs brèves", "sot": "sotte sots sottes", "trompeur": "trompeuse trompeurs trompeuses", "chic": "chics",
+ "complet": "complète complets complètes", "concret": "concrète concrets concrètes", "discret": "discrète discrets discrètes",
+ "inquiet": "inquiète inquiets inquiètes", "secret": "secrète secrets secrètes", "natal": "natale natals natales", "fatal": "fatale fatals fatales",
+ "final": "finale finals finales", "naval": "navale navals navales", "fol": "", "mol": "", "bon": "bonne bons bonnes", "cruel": "cruelle cruels cruelles",
+}
+for a in open(D + "fr_adj.txt").read().split():
+    if "-" in a:
+        continue
+    if a in ADJ:
+        add(a, ADJ[a])
+        continue
+    if a.endswith("e"):
+        f = a
+    elif a.endswith("eux"):
+        f = a[:-1] + "se"
+    elif a.endswith("f"):
+        f = a[:-1] + "ve"
+    elif a.endswith("er"):
+        f = a[:-2] + "ère"
+    elif a.endswith(("el", "en", "on", "et")):
+        f = a + a[-1] + "e"
+    else:
+        f = a + "e"
+    if a[-1] in "sx":
+        mp = a
+    elif a.endswith("al"):
+        mp = a[:-2] + "aux"
+    elif a.endswith("eau"):
+        mp = a + "x"
+    else:
+        mp = a + "s"
+    add(a, f, mp, f + "s")
+
+for w in open(D + "fr_misc.txt").read().split():
+    if "-" not in w and w not in ("aujourd", "quelqu", "d"):
+        add(w)
+
+old = set(open(D + "fr_base.txt").read().split())
+allw = (words | old)
+bad = [w for w in words if not re.fullmatch(r"[a-zà-ÿœ]+", w)]
+assert not bad, bad[:20]
+open(OUT, "w").write("\n".join(sorted(allw)) + "\n")
@@ -0,0 +1,26 @@
+"""Write data/<language>/freq.json, the letter frequencies behind random
+letters, for French, Spanish and German. They are counted over the words of
+<language>_base.txt, each followed by a space.
+
+Run with Python 3 from any directory: python3 data/wordgen/freq.py
+"""
+import collections, json, os
+D = os.path.dirname(os.path.abspath(__file__)) + "/"
+
+for l in ["de", "fr", "es"]:
+    words = open(D + l + "_base.txt").read().split()
+    text = " ".join(words) + " "
+    alphabet = sorted(set(text) - {"-"})
+    counts = collections.Counter(c for c in text if c in alphabet)
+    total = sum(counts.values())
+    letter = {c: round(counts[c] / total, 6) for c in alphabet}
+    following = {}
+    for a in alphabet:
+        pairs = collections.Counter(y for x, y in zip(text, text[1:]) if x == a and y in alphabet)
+        # Light smoothing, so that every transition stays possible
+        total = sum(pairs.values()) + 0.01 * len(alphabet)
+        following[a] = {b: round((pairs[b] + 0.01) / total, 6) for b in alphabet}
+    path = os.path.join(D, "..", l, "freq.json")
+    with open(path, "w") as out:
+        json.dump({"letter_frequency": letter, "next_letter_frequency": following}, out, indent=2, ensure_ascii=False)
+        out.write("\n")
@@ -0,0 +1,49 @@
+//! Simulate typists of different speeds training on each difficulty, and
+//! print when each letter unlocks. Useful for tuning the unlock thresholds.
+//!
+//! Usage: cargo run --example simulate -- [LINES] [SEED]
+
+use linkage::data::keyboard::Layout;
+use linkage::data::language::Language;
+use linkage::data::profile::{Name, Profile};
+use linkage::data::simulation::{Modelled, Simulation};
+use linkage::data::training::Difficulty;
+
+const SPEEDS: &[f64] = &[20.0, 35.0, 50.0];
+const ACCURACY: f64 = 0.95;
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let lines = args.next().and_then(|s| s.parse().ok()).unwrap_or(200);
+    let seed = args.next().and_then(|s| s.parse().ok()).unwrap_or(0);
+    let name = Name::parse("Simulation").expect("Valid profile name");
+
+    for &difficulty in Difficulty::ALL.iter().filter(|d| d.preset().is_some()) {
+        println!("{}", difficulty);
+        for &wpm in SPEEDS {
+            let profile = Profile::new(
+                name.clone(),
+                Layout::default(),
+                Language::default(),
+                difficulty,
+            );
+            let typist = Modelled::new(wpm, ACCURACY, seed);
+            let mut simulation = Simulation::new(profile, typist, seed);
+            let unlocks = simulation.type_lines(lines);
+
+            let schedule = unlocks
+                .iter()
+                .map(|unlock| format!("{}@{}", unlock.letter, unlock.line))
+                .collect::<Vec<_>>()
+                .join(" ");
+            println!(
+                "  {:>2} wpm: {} letters in {} lines, {:.0} minutes: {}",
+                wpm,
+                simulation.profile().state.char_set().len(),
+                lines,
+                simulation.elapsed().as_seconds_f64() / 60.0,
+                schedule
+            );
+        }
+    }
+}
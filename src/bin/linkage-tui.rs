@@ -0,0 +1,328 @@
+//! Terminal front end for Linkage. Trains the same profiles from the same save
+//! file as the graphical application, without needing a window system.
+
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::{
+    Attribute, Color, Print, ResetColor, SetAttribute, SetBackgroundColor, SetForegroundColor,
+};
+use crossterm::{cursor, queue, terminal};
+use itertools::{EitherOrBoth, Itertools};
+use std::io::{self, Write};
+use tokio::runtime::Runtime;
+
+use linkage::data::training::{visible, TriplePoint};
+use linkage::data::{self, profile, Theme};
+
+const USAGE: &str = "Usage: linkage-tui [--seed SEED] [PROFILE NAME]
+
+  --seed SEED  Start from the practice lines generated by SEED";
+const MARGIN: u16 = 2;
+
+struct Tui {
+    runtime: Runtime,
+    profiles: profile::List,
+    theme: Theme,
+    theme_name: String,
+    /// Number of rolling backups to keep
+    backups: u8,
+    accuracy_metric: TriplePoint,
+    /// Problem to show at the bottom of the screen, such as a failed save
+    status: Option<String>,
+}
+
+/// Restores the terminal when dropped, including on panic
+struct RawMode;
+
+fn main() -> io::Result<()> {
+    let mut seed = None;
+    let mut profile_name = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--help" => {
+                println!("{}", USAGE);
+                return Ok(());
+            }
+            "--seed" => match args.next().and_then(|value| value.parse::<u64>().ok()) {
+                Some(value) => seed = Some(value),
+                None => {
+                    eprintln!("--seed needs a whole number\n{}", USAGE);
+                    std::process::exit(1);
+                }
+            },
+            _ => profile_name = Some(arg),
+        }
+    }
+
+    let runtime = tokio::runtime::Builder::new_current_thread().build()?;
+    // Leave an unreadable save file untouched rather than overwrite it
+    let saved = match runtime.block_on(data::Saved::load()) {
+        Ok(saved) => saved,
+        Err(error) => {
+            eprintln!("Could not load your progress: {}", error);
+            eprintln!("Start the desktop app to set the save file aside and start fresh.");
+            std::process::exit(1);
+        }
+    };
+    let data::Saved {
+        profiles,
+        theme_name,
+        backups,
+        ..
+    } = saved;
+    let mut profiles: profile::List = profiles.into();
+
+    if let Some(name) = profile_name {
+        let index = profiles
+            .names()
+            .position(|(profile, _)| profile.to_string() == name);
+        match index {
+            Some(index) => {
+                profiles.select(index);
+            }
+            None => {
+                eprintln!("No profile named \"{}\"", name);
+                let names = profiles
+                    .names()
+                    .map(|(name, _)| name.to_string())
+                    .join(", ");
+                eprintln!("Profiles: {}", names);
+                std::process::exit(1);
+            }
+        }
+    }
+    if let Some(seed) = seed {
+        profiles.active_mut().replay(seed);
+    }
+
+    let accuracy_metric = profiles.active().targets().accuracy_metric();
+    let mut tui = Tui {
+        runtime,
+        profiles,
+        theme: Theme::from_name(&theme_name).unwrap_or_default(),
+        theme_name,
+        backups,
+        accuracy_metric,
+        status: None,
+    };
+
+    let raw_mode = RawMode::enter()?;
+    let result = tui.run();
+    drop(raw_mode);
+    if let Err(error) = tui.save() {
+        eprintln!("Could not save: {}", error);
+        std::process::exit(1);
+    }
+
+    result
+}
+
+impl Tui {
+    fn run(&mut self) -> io::Result<()> {
+        let mut stdout = io::stdout();
+        loop {
+            self.draw(&mut stdout)?;
+
+            let (code, modifiers) = match event::read()? {
+                Event::Key(KeyEvent {
+                    code,
+                    modifiers,
+                    kind: KeyEventKind::Press,
+                    ..
+                }) => (code, modifiers),
+                _ => continue,
+            };
+            let c = match code {
+                KeyCode::Esc => return Ok(()),
+                KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => return Ok(()),
+                KeyCode::Backspace => {
+                    self.profiles.session_mut().backspace();
+                    continue;
+                }
+                KeyCode::Enter => '\n',
+                KeyCode::Tab => '\t',
+                KeyCode::Char(c)
+                    if (c == ' ' || c.is_alphanumeric() || c.is_ascii_punctuation())
+                        && !modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+                {
+                    c
+                }
+                _ => continue,
+            };
+            self.apply_char(c);
+        }
+    }
+
+    /// Type a character, moving on to the next line once this one is complete
+    fn apply_char(&mut self, c: char) {
+        if let Some(line) = self.profiles.session_mut().apply_char(c) {
+            if let Some(words) = self.profiles.active_mut().add_line(line) {
+                self.profiles.session_mut().update_words(words);
+            }
+            let profile = self.profiles.active_mut();
+            profile
+                .session
+                .fill_next_lines(&profile.state, &profile.targets());
+            self.status = self
+                .save()
+                .err()
+                .map(|error| format!("Could not save: {}", error));
+        }
+    }
+
+    fn save(&self) -> Result<(), data::Error> {
+        let saved = data::Saved::new(self.profiles.clone(), &self.theme_name, self.backups);
+        self.runtime.block_on(saved.save())
+    }
+
+    fn draw(&self, out: &mut impl Write) -> io::Result<()> {
+        let theme = &self.theme;
+        let active = self.profiles.active();
+        let session = &active.session;
+        let (_, height) = terminal::size()?;
+
+        queue!(
+            out,
+            SetBackgroundColor(color(theme.bg)),
+            terminal::Clear(terminal::ClearType::All),
+            cursor::MoveTo(MARGIN, 1),
+            SetForegroundColor(color(theme.text)),
+            SetAttribute(Attribute::Dim),
+            Print(format!(
+                "{} \u{00b7} {} \u{00b7} {} \u{00b7} seed {}",
+                active.name,
+                active.layout,
+                active.language,
+                session.seed()
+            )),
+            SetAttribute(Attribute::Reset),
+            SetBackgroundColor(color(theme.bg)),
+            cursor::MoveTo(MARGIN, 3),
+        )?;
+
+        for hit in &session.hits {
+            let hit_color = if hit.is_dirty() {
+                theme.miss
+            } else {
+                theme.hit
+            };
+            queue!(
+                out,
+                SetForegroundColor(color(hit_color)),
+                Print(visible(hit.target()))
+            )?;
+        }
+        let upcoming =
+            std::iter::once(session.active_hit.target()).chain(session.targets.iter().cloned());
+        for (i, result) in session.errors.iter().zip_longest(upcoming).enumerate() {
+            match result {
+                EitherOrBoth::Left(e) | EitherOrBoth::Both(e, _) => {
+                    let c = if *e == ' ' { '\u{2591}' } else { visible(*e) };
+                    queue!(out, SetForegroundColor(color(theme.error)), Print(c))?;
+                }
+                EitherOrBoth::Right(t) if i == 0 => {
+                    queue!(
+                        out,
+                        SetForegroundColor(color(theme.target)),
+                        SetAttribute(Attribute::Underlined),
+                        Print(visible(t)),
+                        SetAttribute(Attribute::NoUnderline),
+                    )?;
+                }
+                EitherOrBoth::Right(t) => {
+                    queue!(
+                        out,
+                        SetForegroundColor(color(theme.text)),
+                        Print(visible(t))
+                    )?;
+                }
+            }
+        }
+
+        for (i, line) in session.next_lines.iter().enumerate() {
+            queue!(
+                out,
+                cursor::MoveTo(MARGIN, 5 + 2 * i as u16),
+                SetForegroundColor(color(theme.text)),
+                Print(line.chars().map(visible).collect::<String>()),
+            )?;
+        }
+
+        let letters_row = 6 + 2 * session.next_lines.len() as u16;
+        queue!(out, cursor::MoveTo(MARGIN, letters_row))?;
+        for (letter, clean) in active.state.clean_letters() {
+            if letter == ' ' {
+                continue;
+            }
+            let metric = theme.metric(self.accuracy_metric.value(clean));
+            queue!(
+                out,
+                SetForegroundColor(color(metric)),
+                Print(format!("{} ", visible(letter)))
+            )?;
+        }
+
+        if let Some(record) = active.state.history().last() {
+            queue!(
+                out,
+                cursor::MoveTo(MARGIN, letters_row + 2),
+                SetForegroundColor(color(theme.text)),
+                Print(format!(
+                    "Last line: {} wpm, {:.0}% accuracy",
+                    f64::from(record.wpm),
+                    100.0 * record.accuracy
+                )),
+            )?;
+        }
+
+        let footer = match &self.status {
+            Some(status) => status.as_str(),
+            None => "Esc to save and quit",
+        };
+        let footer_color = if self.status.is_some() {
+            theme.error
+        } else {
+            theme.text
+        };
+        queue!(
+            out,
+            cursor::MoveTo(MARGIN, height.saturating_sub(2)),
+            SetForegroundColor(color(footer_color)),
+            Print(footer),
+            ResetColor,
+        )?;
+
+        out.flush()
+    }
+}
+
+impl RawMode {
+    fn enter() -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        queue!(io::stdout(), terminal::EnterAlternateScreen, cursor::Hide)?;
+        io::stdout().flush()?;
+
+        Ok(Self)
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        let _ = queue!(
+            io::stdout(),
+            ResetColor,
+            cursor::Show,
+            terminal::LeaveAlternateScreen
+        );
+        let _ = io::stdout().flush();
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+fn color(color: data::theme::Color) -> Color {
+    Color::Rgb {
+        r: (color.r * 255.0).round() as u8,
+        g: (color.g * 255.0).round() as u8,
+        b: (color.b * 255.0).round() as u8,
+    }
+}
@@ -1,13 +1,26 @@
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::collections::HashSet;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use time::OffsetDateTime;
 
+pub mod backup;
+pub mod clock;
+pub mod code;
+pub mod decoration;
 pub mod dictionary;
+pub mod export;
 pub mod keyboard;
+pub mod language;
+pub mod markov;
 pub mod profile;
 pub mod random;
+pub mod recording;
+pub mod simulation;
 pub mod theme;
 pub mod training;
+pub mod typing_test;
 pub mod words;
 pub mod zipper_list;
 
@@ -18,50 +31,262 @@ pub type CharSet = HashSet<char>;
 
 const VERSION: u16 = 1;
 
-#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+/// Upgrades a save file by one version, in place
+type Migration = fn(&mut Value) -> Result<(), Error>;
+
+/// Migrations from each earlier version of the save file, in order: the
+/// migration at index `i` upgrades version `i + 1` to version `i + 2`. Bump
+/// `VERSION` and add a migration here whenever the saved format changes in a
+/// way that `#[serde(default)]` cannot absorb.
+const MIGRATIONS: &[Migration] = &[];
+
+const _: () = assert!(MIGRATIONS.len() + 1 == VERSION as usize);
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Saved {
     version: u16,
     pub profiles: profile::Saved,
     pub theme_name: String,
+    /// Number of rolling backups to keep
+    #[serde(default = "default_backups")]
+    pub backups: u8,
 }
 
 impl Saved {
-    pub fn new(profiles: profile::List, theme_name: &str) -> Self {
+    pub fn new(profiles: profile::List, theme_name: &str, backups: u8) -> Self {
         Self {
             version: VERSION,
             profiles: profiles.into(),
             theme_name: theme_name.to_string(),
+            backups,
         }
     }
 
+    /// Load saved data, upgrading it from earlier versions. Nothing having
+    /// been saved yet is not an error.
     pub async fn load() -> Result<Self, Error> {
         let path = Self::path().await.ok_or(Error::Corrupted)?;
-        let data = tokio::fs::read(path).await.map_err(Error::FileSystem)?;
+        let data = match tokio::fs::read(path).await {
+            Ok(data) => data,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
+                return Ok(Self::default())
+            }
+            Err(error) => return Err(Error::FileSystem(error)),
+        };
         let data = String::from_utf8_lossy(&data);
-        serde_json::from_str(&data).map_err(Error::Serde)
+        let mut value: Value = serde_json::from_str(&data).map_err(Error::Serde)?;
+        migrate(&mut value)?;
+        serde_json::from_value(value).map_err(Error::Serde)
+    }
+
+    /// Move an unreadable save file out of the way so that a fresh one can
+    /// be written, returning where the old file was kept
+    pub async fn set_aside() -> Result<PathBuf, Error> {
+        let path = Self::path().await.ok_or(Error::Corrupted)?;
+        let mut kept = path.clone();
+        kept.set_file_name(format!(
+            "save-{}.dat.unreadable",
+            OffsetDateTime::now_utc().unix_timestamp()
+        ));
+        tokio::fs::rename(&path, &kept)
+            .await
+            .map_err(Error::FileSystem)?;
+
+        Ok(kept)
     }
 
+    /// Save atomically, so that a crash or full disk part way through leaves
+    /// the previous save in place. The previous save is also backed up when
+    /// a backup is due.
     pub async fn save(&self) -> Result<(), Error> {
         let path = Self::path().await.ok_or(Error::Corrupted)?;
         let data = serde_json::to_string(&self).map_err(Error::Serde)?;
 
-        tokio::fs::write(path, data.into_bytes())
+        backup::create(&path, self.backups, false).await?;
+        write_atomic(&path, data.as_bytes()).await
+    }
+
+    /// Replace the save file with a backup. The current save is backed up
+    /// first, so a restore can itself be undone.
+    pub async fn restore(backup: &backup::Backup, backups: u8) -> Result<(), Error> {
+        let path = Self::path().await.ok_or(Error::Corrupted)?;
+        // Read before backing up, which may remove the oldest backups
+        let data = tokio::fs::read(&backup.path)
             .await
-            .map_err(Error::FileSystem)
+            .map_err(Error::FileSystem)?;
+
+        backup::create(&path, backups.max(1), true).await?;
+        write_atomic(&path, &data).await
     }
 
     async fn path() -> Option<PathBuf> {
-        let mut path = dirs_next::data_dir()?;
-        path.push("Linkage");
-        tokio::fs::create_dir_all(&path).await.ok()?;
+        let mut path = dir().await?;
         path.push("save.dat");
         Some(path)
     }
 }
 
+impl Default for Saved {
+    fn default() -> Self {
+        Self {
+            version: VERSION,
+            profiles: profile::Saved::default(),
+            theme_name: String::default(),
+            backups: backup::DEFAULT_KEEP,
+        }
+    }
+}
+
+fn default_backups() -> u8 {
+    backup::DEFAULT_KEEP
+}
+
+/// Write to a temporary file, then rename it over the destination. Each
+/// write gets its own temporary file, so saves that overlap cannot write
+/// into each other's.
+async fn write_atomic(path: &Path, data: &[u8]) -> Result<(), Error> {
+    static WRITES: AtomicU64 = AtomicU64::new(0);
+
+    let mut temporary = path.as_os_str().to_owned();
+    temporary.push(format!(
+        ".{}.{}.tmp",
+        std::process::id(),
+        WRITES.fetch_add(1, Ordering::Relaxed)
+    ));
+    let temporary = PathBuf::from(temporary);
+
+    let result = write_and_rename(&temporary, path, data).await;
+    if result.is_err() {
+        let _ = tokio::fs::remove_file(&temporary).await;
+    }
+    result
+}
+
+async fn write_and_rename(temporary: &Path, path: &Path, data: &[u8]) -> Result<(), Error> {
+    tokio::fs::write(temporary, data)
+        .await
+        .map_err(Error::FileSystem)?;
+    tokio::fs::OpenOptions::new()
+        .write(true)
+        .open(temporary)
+        .await
+        .map_err(Error::FileSystem)?
+        .sync_all()
+        .await
+        .map_err(Error::FileSystem)?;
+    tokio::fs::rename(temporary, path)
+        .await
+        .map_err(Error::FileSystem)
+}
+
+/// Bring a save file from any earlier version up to `VERSION`
+fn migrate(value: &mut Value) -> Result<(), Error> {
+    let version = value
+        .get("version")
+        .and_then(Value::as_u64)
+        .ok_or(Error::Corrupted)?;
+    if version > VERSION as u64 {
+        return Err(Error::Newer(version));
+    }
+
+    for migration in MIGRATIONS.iter().skip(version.saturating_sub(1) as usize) {
+        migration(value)?;
+    }
+    value["version"] = VERSION.into();
+
+    Ok(())
+}
+
+/// The Linkage data directory, created if it does not exist yet
+async fn dir() -> Option<PathBuf> {
+    let mut path = dirs_next::data_dir()?;
+    path.push("Linkage");
+    tokio::fs::create_dir_all(&path).await.ok()?;
+    Some(path)
+}
+
 #[derive(Debug)]
 pub enum Error {
     Corrupted,
     FileSystem(std::io::Error),
+    /// A profile name that is empty, too long or starts or ends with spaces
+    InvalidName(String),
+    /// Saved by a newer version of Linkage than this one
+    Newer(u64),
     Serde(serde_json::Error),
 }
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Corrupted => write!(f, "The save file is corrupted"),
+            Error::FileSystem(error) => write!(f, "File system error: {}", error),
+            Error::InvalidName(name) => write!(f, "\"{}\" is not a valid profile name", name),
+            Error::Newer(version) => write!(
+                f,
+                "The save file is from a newer version of Linkage (version {}, expected {} or earlier)",
+                version, VERSION
+            ),
+            Error::Serde(error) => write!(f, "Could not parse the save file: {}", error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overlapping_writes_all_land() {
+        let directory = std::env::temp_dir().join(format!("linkage-{}-writes", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let path = directory.join("profiles.json");
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let results = runtime.block_on(async {
+            let writes: Vec<_> = (0..8)
+                .map(|i| {
+                    let path = path.clone();
+                    tokio::spawn(
+                        async move { write_atomic(&path, format!("{}", i).as_bytes()).await },
+                    )
+                })
+                .collect();
+            let mut results = Vec::new();
+            for write in writes {
+                results.push(write.await.unwrap());
+            }
+            results
+        });
+        let left: Vec<_> = std::fs::read_dir(&directory)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        std::fs::remove_dir_all(&directory).unwrap();
+
+        assert!(results.iter().all(Result::is_ok), "{:?}", results);
+        assert_eq!(left, ["profiles.json"]);
+    }
+
+    #[test]
+    fn first_save_files_are_upgraded() {
+        // The first release saved version 0 until something replaced its
+        // default save
+        let mut value: Value =
+            serde_json::from_str(include_str!("data/fixtures/save-v0.json")).unwrap();
+        migrate(&mut value).unwrap();
+        assert_eq!(value["version"], VERSION);
+
+        let saved: Saved = serde_json::from_value(value).unwrap();
+        let profiles: profile::List = saved.profiles.into();
+        let profile = profiles.active();
+        assert_eq!(profile.name.to_string(), "Default Profile");
+        assert_eq!(
+            profile.state.char_set(),
+            CharSet::from(['a', 's', 'd', 'f', 'e', 'j'])
+        );
+        assert_eq!(saved.backups, backup::DEFAULT_KEEP);
+    }
+}
@@ -0,0 +1,139 @@
+use super::Error;
+use std::path::{Path, PathBuf};
+use time::{Duration, OffsetDateTime};
+
+/// Number of backups kept unless configured otherwise
+pub const DEFAULT_KEEP: u8 = 5;
+/// Most backups that can be configured
+pub const MAX_KEEP: u8 = 20;
+/// Minimum time between automatic backups, so that saving after every line
+/// does not push out older backups
+const INTERVAL: Duration = Duration::hours(1);
+/// Backups are named `save-<milliseconds since the Unix epoch>.dat`
+const PREFIX: &str = "save-";
+const EXTENSION: &str = "dat";
+
+/// Timestamped copy of the save file
+#[derive(Debug, Clone)]
+pub struct Backup {
+    pub path: PathBuf,
+    pub time: OffsetDateTime,
+}
+
+/// Every backup in the data directory, newest first
+pub async fn list() -> Vec<Backup> {
+    let Some(dir) = dir().await else {
+        return Vec::new();
+    };
+    let mut entries = match tokio::fs::read_dir(&dir).await {
+        Ok(entries) => entries,
+        Err(error) => {
+            eprintln!("Could not read backups from {}: {}", dir.display(), error);
+            return Vec::new();
+        }
+    };
+
+    let mut backups = Vec::new();
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        if let Some(backup) = Backup::parse(entry.path()) {
+            backups.push(backup);
+        }
+    }
+    backups.sort_by_key(|backup| std::cmp::Reverse(backup.time));
+
+    backups
+}
+
+/// Copy the save file into the backups directory when the newest backup is
+/// old enough, or always when `force` is set, then drop the oldest backups
+/// beyond `keep`
+pub(super) async fn create(save: &Path, keep: u8, force: bool) -> Result<(), Error> {
+    let backups = list().await;
+    let now = OffsetDateTime::now_utc();
+    let recent = backups
+        .first()
+        .is_some_and(|newest| now - newest.time < INTERVAL);
+
+    let mut kept = backups.len();
+    if keep > 0 && (force || !recent) && tokio::fs::metadata(save).await.is_ok() {
+        let dir = dir().await.ok_or(Error::Corrupted)?;
+        let path = dir.join(format!(
+            "{}{}.{}",
+            PREFIX,
+            now.unix_timestamp_nanos() / 1_000_000,
+            EXTENSION
+        ));
+        tokio::fs::copy(save, &path)
+            .await
+            .map_err(Error::FileSystem)?;
+        kept += 1;
+    }
+
+    // The list is newest first, and excludes any backup just made
+    let excess = kept.saturating_sub(keep as usize);
+    for backup in backups.iter().rev().take(excess) {
+        tokio::fs::remove_file(&backup.path)
+            .await
+            .map_err(Error::FileSystem)?;
+    }
+
+    Ok(())
+}
+
+impl Backup {
+    /// Recognise a backup from its file name
+    fn parse(path: PathBuf) -> Option<Self> {
+        let stem = path.file_stem()?.to_str()?;
+        let milliseconds: i128 = stem.strip_prefix(PREFIX)?.parse().ok()?;
+        if path.extension()?.to_str()? != EXTENSION {
+            return None;
+        }
+
+        Some(Self {
+            time: OffsetDateTime::from_unix_timestamp_nanos(milliseconds * 1_000_000),
+            path,
+        })
+    }
+}
+
+impl std::fmt::Display for Backup {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} UTC", self.time.format("%Y-%m-%d %H:%M:%S"))
+    }
+}
+
+/// The backups directory, created if it does not exist yet
+async fn dir() -> Option<PathBuf> {
+    let mut path = super::dir().await?;
+    path.push("backups");
+    tokio::fs::create_dir_all(&path).await.ok()?;
+    Some(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backups_are_named_by_time() {
+        let backup = Backup::parse(PathBuf::from("backups/save-1500.dat")).unwrap();
+
+        assert_eq!(
+            backup.time,
+            OffsetDateTime::unix_epoch() + Duration::milliseconds(1500)
+        );
+        assert_eq!(backup.to_string(), "1970-01-01 00:00:01 UTC");
+    }
+
+    #[test]
+    fn other_files_are_not_backups() {
+        for name in [
+            "save.dat",
+            "save-x.dat",
+            "save-1500.json",
+            "profile-1500.dat",
+        ] {
+            assert!(Backup::parse(PathBuf::from(name)).is_none(), "{}", name);
+        }
+    }
+}
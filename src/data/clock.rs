@@ -0,0 +1,60 @@
+use time::{Duration, Instant, OffsetDateTime};
+
+/// Source of time for a training session. Keystrokes are timed against it,
+/// so a simulated typist can move time forward without waiting.
+#[derive(Debug, Clone)]
+pub enum Clock {
+    /// Real time
+    System { start: Instant },
+    /// Time that only passes when the clock is advanced
+    Manual {
+        start: OffsetDateTime,
+        elapsed: Duration,
+    },
+}
+
+impl Clock {
+    pub fn system() -> Self {
+        Self::System {
+            start: Instant::now(),
+        }
+    }
+
+    /// Clock stopped at `start`, until advanced
+    pub fn manual(start: OffsetDateTime) -> Self {
+        Self::Manual {
+            start,
+            elapsed: Duration::zero(),
+        }
+    }
+
+    /// Time since the clock was started
+    pub fn elapsed(&self) -> Duration {
+        match self {
+            Clock::System { start } => start.elapsed(),
+            Clock::Manual { elapsed, .. } => *elapsed,
+        }
+    }
+
+    /// The date and time now, by this clock
+    pub fn now_utc(&self) -> OffsetDateTime {
+        match self {
+            Clock::System { .. } => OffsetDateTime::now_utc(),
+            Clock::Manual { start, elapsed } => *start + *elapsed,
+        }
+    }
+
+    /// Move a manual clock forward. Real time cannot be advanced, so the
+    /// system clock is unaffected.
+    pub fn advance(&mut self, by: Duration) {
+        if let Clock::Manual { elapsed, .. } = self {
+            *elapsed += by;
+        }
+    }
+}
+
+impl Default for Clock {
+    fn default() -> Self {
+        Self::system()
+    }
+}
@@ -0,0 +1,152 @@
+use rand::Rng;
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+
+/// File extensions recognised as source code
+const EXTENSIONS: &[&str] = &[
+    "c", "cc", "cpp", "cs", "css", "go", "h", "hpp", "html", "java", "js", "json", "kt", "lua",
+    "py", "rb", "rs", "sh", "sql", "swift", "toml", "ts", "yaml", "yml",
+];
+/// Directories that hold generated or third-party code
+const SKIPPED_DIRS: &[&str] = &["node_modules", "target", "vendor", "__pycache__"];
+/// How deep to look into a directory for source files
+const MAX_DEPTH: usize = 6;
+const MAX_FILES: usize = 500;
+/// Consecutive lines taken from a file before jumping elsewhere
+const SNIPPET_LINES: usize = 16;
+const TAB_WIDTH: usize = 4;
+
+/// Snippets of source code read from local files, typed line by line with
+/// their indentation and line breaks
+#[derive(Debug, Clone)]
+pub struct Code {
+    /// Lines of each file, with blank and untypeable lines removed
+    files: Vec<Vec<String>>,
+    /// Remaining pieces of the current snippet
+    pending: VecDeque<String>,
+}
+
+impl Code {
+    /// Read source code from a single file, or from every recognised file
+    /// beneath a directory
+    pub fn load(path: &Path) -> Code {
+        let mut paths = Vec::new();
+        if path.is_dir() {
+            collect(path, 0, &mut paths);
+            // Directory order varies between file systems
+            paths.sort();
+        } else {
+            paths.push(path.to_path_buf());
+        }
+
+        let files = paths
+            .iter()
+            .filter_map(|path| match std::fs::read_to_string(path) {
+                Ok(text) => Some(lines(&text)),
+                Err(error) => {
+                    eprintln!("Could not read code from {}: {}", path.display(), error);
+                    None
+                }
+            })
+            .filter(|lines| !lines.is_empty())
+            .collect();
+
+        Code {
+            files,
+            pending: VecDeque::new(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Next line of code, at most `length` characters long. The end of a
+    /// source line is a newline; longer lines are broken at a space.
+    pub fn line<R: Rng>(&mut self, length: usize, rng: &mut R) -> String {
+        if self.pending.is_empty() {
+            self.next_snippet(length, rng);
+        }
+
+        self.pending.pop_front().unwrap_or_default()
+    }
+
+    fn next_snippet<R: Rng>(&mut self, length: usize, rng: &mut R) {
+        if self.files.is_empty() {
+            return;
+        }
+        let file = &self.files[rng.gen_range(0..self.files.len())];
+        let start = rng.gen_range(0..file.len().saturating_sub(SNIPPET_LINES).max(1));
+
+        for line in file.iter().skip(start).take(SNIPPET_LINES) {
+            self.pending.extend(wrap(line, length));
+        }
+    }
+}
+
+/// Recursively gather source files, skipping hidden and generated directories
+fn collect(dir: &Path, depth: usize, paths: &mut Vec<PathBuf>) {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(error) => {
+            eprintln!("Could not read directory {}: {}", dir.display(), error);
+            return;
+        }
+    };
+
+    for entry in entries.flatten() {
+        if paths.len() >= MAX_FILES {
+            return;
+        }
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.starts_with('.') {
+            continue;
+        }
+
+        if path.is_dir() {
+            if depth < MAX_DEPTH && !SKIPPED_DIRS.contains(&name.as_str()) {
+                collect(&path, depth + 1, paths);
+            }
+        } else if path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .is_some_and(|extension| EXTENSIONS.contains(&extension))
+        {
+            paths.push(path);
+        }
+    }
+}
+
+/// Normalise source text into typeable lines: tabs become spaces, trailing
+/// whitespace is dropped, and blank lines or lines with characters that
+/// cannot be typed on the keyboard are skipped
+fn lines(text: &str) -> Vec<String> {
+    text.lines()
+        .map(|line| line.replace('\t', &" ".repeat(TAB_WIDTH)))
+        .map(|line| line.trim_end().to_string())
+        .filter(|line| !line.is_empty())
+        .filter(|line| line.chars().all(|c| c == ' ' || c.is_ascii_graphic()))
+        .collect()
+}
+
+/// Break a source line into pieces no longer than `length`, ending the last
+/// piece with a newline
+fn wrap(line: &str, length: usize) -> Vec<String> {
+    let mut pieces = Vec::new();
+    let mut rest: Vec<char> = line.chars().collect();
+
+    while rest.len() >= length {
+        // Keep the space at the end of the piece, as between words
+        let split = rest[1..length]
+            .iter()
+            .rposition(|&c| c == ' ')
+            .map_or(length - 1, |i| i + 2);
+        pieces.push(rest.drain(..split).collect());
+    }
+    let mut last: String = rest.into_iter().collect();
+    last.push('\n');
+    pieces.push(last);
+
+    pieces
+}
@@ -0,0 +1,174 @@
+use super::CharSet;
+use itertools::Itertools;
+use rand::Rng;
+
+/// Chance that a word is followed by a punctuation mark
+const PUNCTUATION_PROBABILITY: f64 = 0.2;
+/// Chance that a word is made possessive with an apostrophe
+const APOSTROPHE_PROBABILITY: f64 = 0.05;
+/// Chance that a word is followed by a number, as in "page 12"
+const NUMBER_PROBABILITY: f64 = 0.1;
+/// Chance of using a character that needs improvement, when one applies
+const FOCUS_PROBABILITY: f64 = 0.5;
+
+/// Marks that may follow a word. A hyphen joins two words together.
+const TRAILING: &[char] = &['.', ',', '?', '!', ';', ':', '-'];
+/// Marks that end a sentence, so the next word is capitalised
+const SENTENCE_END: &[char] = &['.', '?', '!'];
+
+/// Decorate a plain lowercase word with whichever capital letters,
+/// punctuation and digits are present in the character set. The word itself
+/// is kept: a capital only ever starts it, and numbers and marks go after it.
+/// Characters in `focus` are favoured so that weak characters show up often
+/// enough.
+pub fn decorate<R: Rng>(
+    word: &str,
+    sentence_start: bool,
+    char_set: &CharSet,
+    focus: &CharSet,
+    rng: &mut R,
+) -> String {
+    let mut chars = word.chars();
+    let Some(first) = chars.next() else {
+        return String::new();
+    };
+
+    // Sentences start with a capital. Once sentences can end, a weak capital
+    // is practised by ending more of them; before then, it starts a word as
+    // a name would.
+    let sentence_marks: Vec<char> = SENTENCE_END
+        .iter()
+        .filter(|c| char_set.contains(c))
+        .cloned()
+        .collect();
+    let capital: String = first.to_uppercase().collect();
+    let unlocked = capital.chars().all(|c| char_set.contains(&c));
+    let weak = capital.chars().any(|c| focus.contains(&c));
+    let named = weak && sentence_marks.is_empty() && rng.gen_bool(FOCUS_PROBABILITY);
+    let mut decorated = if unlocked && (sentence_start || named) {
+        capital + chars.as_str()
+    } else {
+        word.to_string()
+    };
+
+    if char_set.contains(&'\'') && char_set.contains(&'s') {
+        let probability = if focus.contains(&'\'') {
+            FOCUS_PROBABILITY
+        } else {
+            APOSTROPHE_PROBABILITY
+        };
+        if rng.gen_bool(probability) {
+            decorated.push_str("'s");
+        }
+    }
+
+    let digits: Vec<char> = char_set
+        .iter()
+        .filter(|c| c.is_ascii_digit())
+        .cloned()
+        .sorted()
+        .collect();
+    if !digits.is_empty() {
+        let probability = if digits.iter().any(|d| focus.contains(d)) {
+            FOCUS_PROBABILITY
+        } else {
+            NUMBER_PROBABILITY
+        };
+        if rng.gen_bool(probability) {
+            let length = rng.gen_range(1..=4);
+            decorated.push(' ');
+            decorated.extend((0..length).map(|_| digits[rng.gen_range(0..digits.len())]));
+        }
+    }
+
+    let marks: Vec<char> = TRAILING
+        .iter()
+        .filter(|c| char_set.contains(c))
+        .cloned()
+        .collect();
+    let weak_marks: Vec<char> = marks
+        .iter()
+        .filter(|c| focus.contains(c))
+        .cloned()
+        .collect();
+    let weak_capital = focus.iter().any(|c| c.is_uppercase());
+    if !weak_marks.is_empty() && rng.gen_bool(FOCUS_PROBABILITY) {
+        decorated.push(weak_marks[rng.gen_range(0..weak_marks.len())]);
+    } else if weak_capital && !sentence_marks.is_empty() && rng.gen_bool(FOCUS_PROBABILITY) {
+        decorated.push(sentence_marks[rng.gen_range(0..sentence_marks.len())]);
+    } else if !marks.is_empty() && rng.gen_bool(PUNCTUATION_PROBABILITY) {
+        decorated.push(marks[rng.gen_range(0..marks.len())]);
+    }
+
+    decorated
+}
+
+/// Whether the word closes a sentence, so the next one should be capitalised
+pub fn ends_sentence(word: &str) -> bool {
+    word.ends_with(SENTENCE_END)
+}
+
+/// Append a decorated word to the line, followed by a space unless the word
+/// ends with a hyphen joining it to the next one
+pub fn push_word(line: &mut String, word: &str) {
+    line.push_str(word);
+    if !word.ends_with('-') {
+        line.push(' ');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn decorated(sentence_start: bool, letters: &str, focus: &str) -> Vec<String> {
+        let char_set: CharSet = letters.chars().collect();
+        let focus: CharSet = focus.chars().collect();
+        let mut rng = StdRng::seed_from_u64(7);
+        (0..200)
+            .map(|_| decorate("jade", sentence_start, &char_set, &focus, &mut rng))
+            .collect()
+    }
+
+    #[test]
+    fn sentences_start_with_a_capital() {
+        let letters = "asdfejASDFEJ. ";
+
+        assert!(decorated(true, letters, "")
+            .iter()
+            .all(|word| word.starts_with("Jade")));
+        assert!(decorated(false, letters, "")
+            .iter()
+            .all(|word| word.starts_with("jade")));
+    }
+
+    #[test]
+    fn weak_capitals_only_start_words() {
+        let words = decorated(false, "asdfejASDFEJ ", "J");
+
+        assert!(words.iter().any(|word| word == "Jade"));
+        assert!(words.iter().all(|word| word == "Jade" || word == "jade"));
+    }
+
+    #[test]
+    fn weak_capitals_end_sentences_once_they_can() {
+        let words = decorated(false, "asdfejASDFEJ. ", "J");
+
+        assert!(words.iter().any(|word| word == "jade."));
+        assert!(words.iter().all(|word| word == "jade" || word == "jade."));
+    }
+
+    #[test]
+    fn numbers_follow_words() {
+        let words = decorated(false, "asdfej47 ", "");
+
+        assert!(words.iter().any(|word| word != "jade"));
+        for word in words {
+            let (first, rest) = word.split_once(' ').unwrap_or((&word, ""));
+            assert_eq!(first, "jade");
+            assert!(rest.chars().all(|c| c == '4' || c == '7'), "{:?}", word);
+        }
+    }
+}
@@ -1,31 +1,84 @@
+use super::decoration;
+use super::language::Language;
+use super::training::Bigram;
 use super::CharSet;
+use itertools::Itertools;
 use rand::distributions::{Distribution, Uniform};
-use rand::rngs::ThreadRng;
+use rand::Rng;
 
+const DE_WORDS: &[u8] = include_bytes!("../../data/de/popular.txt");
 const EN_WORDS: &[u8] = include_bytes!("../../data/en/popular.txt");
+const ES_WORDS: &[u8] = include_bytes!("../../data/es/popular.txt");
+const FR_WORDS: &[u8] = include_bytes!("../../data/fr/popular.txt");
 
 #[derive(Debug, Clone)]
 pub struct Dictionary {
     words: Vec<String>,
-    dist: Uniform<usize>,
-    rng: ThreadRng,
+    /// Characters available for decorating words
+    char_set: CharSet,
+    /// Picks a word, while there are any
+    dist: Option<Uniform<usize>>,
     unit_dist: Uniform<f32>,
 }
 
 impl Dictionary {
-    pub fn load() -> Dictionary {
-        let raw = std::str::from_utf8(EN_WORDS).expect("Loading raw words");
+    pub fn load(language: Language) -> Dictionary {
+        let raw = match language {
+            Language::English => EN_WORDS,
+            Language::French => FR_WORDS,
+            Language::German => DE_WORDS,
+            Language::Spanish => ES_WORDS,
+        };
+        let raw = std::str::from_utf8(raw).expect("Loading raw words");
         let words: Vec<String> = raw
             .split('\n')
-            .filter_map(|s| (!s.is_empty()).then(|| s.trim().to_string()))
+            .filter(|s| !s.is_empty())
+            .map(|s| s.trim().to_string())
             .collect();
-        let dist = Uniform::new(0, words.len());
+
+        Self::from_words(words)
+    }
+
+    /// Build a word list from arbitrary text, such as a document or a list of
+    /// words. Surrounding punctuation is stripped and words are lowercased.
+    pub fn from_text(text: &str) -> Dictionary {
+        let words: Vec<String> = text
+            .split_whitespace()
+            .map(|token| {
+                token
+                    .trim_matches(|c: char| !c.is_alphanumeric())
+                    .to_lowercase()
+            })
+            .filter(|word| !word.is_empty())
+            .unique()
+            .collect();
+
+        Self::from_words(words)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    pub fn words(&self) -> &[String] {
+        &self.words
+    }
+
+    /// Whether any word uses only characters in the set
+    pub fn fits(&self, char_set: &CharSet) -> bool {
+        self.words
+            .iter()
+            .any(|word| word.chars().all(|c| char_set.contains(&c)))
+    }
+
+    fn from_words(words: Vec<String>) -> Dictionary {
+        let dist = index_dist(&words);
         let unit_dist = Uniform::new_inclusive(0.0, 1.0);
 
         Dictionary {
             words,
+            char_set: CharSet::new(),
             dist,
-            rng: rand::thread_rng(),
             unit_dist,
         }
     }
@@ -39,12 +92,12 @@ impl Dictionary {
             .filter(|word| word.chars().all(|c| char_set.contains(&c)))
             .cloned()
             .collect();
-        let dist = Uniform::new(0, words.len());
+        let dist = index_dist(&words);
 
         Dictionary {
             words,
+            char_set: char_set.clone(),
             dist,
-            rng: rand::thread_rng(),
             unit_dist: self.unit_dist,
         }
     }
@@ -58,12 +111,12 @@ impl Dictionary {
             .cloned()
             .collect();
         (!words.is_empty()).then(|| {
-            let dist = Uniform::new(0, words.len());
+            let dist = index_dist(&words);
 
             Dictionary {
                 words,
+                char_set: self.char_set.clone(),
                 dist,
-                rng: rand::thread_rng(),
                 unit_dist: self.unit_dist,
             }
         })
@@ -78,39 +131,56 @@ impl Dictionary {
             .cloned()
             .collect();
         (!words.is_empty()).then(|| {
-            let dist = Uniform::new(0, words.len());
+            let dist = index_dist(&words);
 
             Dictionary {
                 words,
+                char_set: self.char_set.clone(),
                 dist,
-                rng: rand::thread_rng(),
                 unit_dist: self.unit_dist,
             }
         })
     }
 
     /// Random line of words at most `length` characters long
-    pub fn line(&mut self, length: usize, needs_improvement: &CharSet) -> String {
+    pub fn line<R: Rng>(
+        &self,
+        length: usize,
+        needs_improvement: &CharSet,
+        weak_bigrams: &[Bigram],
+        rng: &mut R,
+    ) -> String {
         let mut best = String::new();
         let mut metric = i32::MAX;
+        let focused = !needs_improvement.is_empty() || !weak_bigrams.is_empty();
 
         for _ in 0..100 {
             let mut line = String::new();
             let mut line_len = 0;
+            let mut sentence_start = true;
             while line_len <= length {
-                let word = self.sample();
+                let word = self.sample(rng);
+                let word = decoration::decorate(
+                    &word,
+                    sentence_start,
+                    &self.char_set,
+                    needs_improvement,
+                    rng,
+                );
                 let word_chars = word.chars().collect::<CharSet>();
 
                 // With a random probability, discard the word if it contains
-                // none of the lowest accuracy letters.
+                // none of the lowest accuracy letters or weakest transitions,
+                // when there are any to focus on.
                 let contains_needs_improvement =
-                    needs_improvement.intersection(&word_chars).any(|_| true);
-                if !contains_needs_improvement && self.unit_dist.sample(&mut self.rng) < 0.99 {
+                    needs_improvement.intersection(&word_chars).any(|_| true)
+                        || weak_bigrams.iter().any(|bigram| bigram.in_word(&word));
+                if focused && !contains_needs_improvement && self.unit_dist.sample(rng) < 0.99 {
                     continue;
                 }
 
-                line.push_str(&word);
-                line.push(' ');
+                decoration::push_word(&mut line, &word);
+                sentence_start = decoration::ends_sentence(&word);
 
                 line_len = line.chars().count();
                 if line_len == length {
@@ -127,11 +197,17 @@ impl Dictionary {
         best
     }
 
-    fn sample(&mut self) -> String {
-        if self.words.is_empty() {
+    fn sample<R: Rng>(&self, rng: &mut R) -> String {
+        let Some(dist) = self.dist else {
             return "error".to_string();
-        }
-        let index = self.dist.sample(&mut self.rng).min(self.words.len() - 1);
+        };
+        let index = dist.sample(rng).min(self.words.len() - 1);
         self.words[index].clone()
     }
 }
+
+/// Picks an index into the words. `Uniform` cannot be built over an empty
+/// range, so there is none for an empty list.
+fn index_dist(words: &[String]) -> Option<Uniform<usize>> {
+    (!words.is_empty()).then(|| Uniform::new(0, words.len()))
+}
@@ -0,0 +1,297 @@
+use super::keyboard::Layout;
+use super::language::Language;
+use super::profile::Name;
+use super::training::{Event, State};
+use super::Error;
+use itertools::Itertools;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use time::{Format, OffsetDateTime};
+
+/// Snapshot of a profile's training statistics, ready to be written out for
+/// analysis elsewhere
+#[derive(Debug, Clone, Serialize)]
+pub struct Statistics {
+    pub profile: Name,
+    /// When the snapshot was taken, in RFC 3339 format
+    pub exported: String,
+    pub letters: Vec<Letter>,
+    pub unlocks: Vec<Unlock>,
+    pub history: Vec<Line>,
+}
+
+/// One row of `<profile>-letters.csv`
+#[derive(Debug, Clone, Serialize)]
+pub struct Letter {
+    /// The character typed
+    pub letter: char,
+    /// Whether the character is in the training set. Symbols typed in source
+    /// code are tracked without being unlocked.
+    pub unlocked: bool,
+    /// Arithmetic mean speed over recent hits, in words per minute
+    pub wpm_mean: f64,
+    /// Harmonic mean speed over recent hits, in words per minute. This is the
+    /// speed compared against the difficulty to unlock the next letter.
+    pub wpm_harmonic_mean: f64,
+    /// Smoothed proportion of hits typed without a miss, from 0 to 1
+    pub clean: f32,
+}
+
+/// One row of `<profile>-unlocks.csv`
+#[derive(Debug, Clone, Serialize)]
+pub struct Unlock {
+    /// The character added to the training set
+    pub letter: char,
+    /// When it was added, in RFC 3339 format
+    pub time: String,
+    /// Whether it was added by hand rather than by reaching the targets
+    pub manual: bool,
+}
+
+/// One row of `<profile>-history.csv`
+#[derive(Debug, Clone, Serialize)]
+pub struct Line {
+    /// When the line was completed, in RFC 3339 format
+    pub time: String,
+    /// Time spent typing the line
+    pub duration_seconds: f64,
+    /// Number of characters typed
+    pub characters: u16,
+    /// Number of incorrect keys pressed
+    pub errors: u16,
+    /// Typing speed across the line, in words per minute
+    pub wpm: f64,
+    /// Proportion of characters typed without a miss, from 0 to 1
+    pub accuracy: f32,
+    /// Number of characters in the training set at the time
+    pub unlocked: u16,
+}
+
+/// A record that can be written as a row of comma-separated values
+trait Row {
+    const HEADER: &'static str;
+
+    fn fields(&self) -> Vec<String>;
+}
+
+impl Statistics {
+    pub fn new(name: &Name, state: &State, layout: &Layout, language: Language) -> Self {
+        let char_set = state.char_set();
+        let clean: HashMap<char, f32> = state.clean_letters().into_iter().collect();
+
+        // Letters in the order they unlock, followed by any other symbols
+        let order = layout.letter_order(language);
+        let letters = order
+            .iter()
+            .cloned()
+            .chain(state.timings.keys().cloned().sorted())
+            .chain(clean.keys().cloned().sorted())
+            .unique()
+            .filter(|letter| state.timings.contains_key(letter) || clean.contains_key(letter))
+            .map(|letter| {
+                let stats = state.timings.get(&letter);
+                Letter {
+                    letter,
+                    unlocked: char_set.contains(&letter),
+                    wpm_mean: stats.map(|s| s.wpm_mean.into()).unwrap_or_default(),
+                    wpm_harmonic_mean: stats
+                        .map(|s| s.wpm_harmonic_mean.into())
+                        .unwrap_or_default(),
+                    clean: clean.get(&letter).cloned().unwrap_or_default(),
+                }
+            })
+            .collect();
+
+        let unlocks = state
+            .events()
+            .iter()
+            .filter_map(|event| match event {
+                Event::Unlock {
+                    letter,
+                    time,
+                    manual,
+                } => Some(Unlock {
+                    letter: *letter,
+                    time: timestamp(*time),
+                    manual: *manual,
+                }),
+                _ => None,
+            })
+            .collect();
+
+        let history = state
+            .history()
+            .iter()
+            .map(|record| Line {
+                time: timestamp(record.time),
+                duration_seconds: record.duration.as_seconds_f64(),
+                characters: record.characters,
+                errors: record.errors,
+                wpm: record.wpm.into(),
+                accuracy: record.accuracy,
+                unlocked: record.unlocked,
+            })
+            .collect();
+
+        Self {
+            profile: name.clone(),
+            exported: timestamp(OffsetDateTime::now_utc()),
+            letters,
+            unlocks,
+            history,
+        }
+    }
+
+    /// Write CSV files for letters, unlocks and history, plus one JSON file
+    /// holding all three, into the directory. Returns the files written.
+    pub async fn write(&self, dir: &Path) -> Result<Vec<PathBuf>, Error> {
+        let base = self.profile.file_name();
+        let files = [
+            (format!("{}-letters.csv", base), csv(&self.letters)),
+            (format!("{}-unlocks.csv", base), csv(&self.unlocks)),
+            (format!("{}-history.csv", base), csv(&self.history)),
+            (
+                format!("{}.json", base),
+                serde_json::to_string_pretty(self).map_err(Error::Serde)?,
+            ),
+        ];
+
+        let mut written = Vec::new();
+        for (name, data) in files {
+            let path = dir.join(name);
+            super::write_atomic(&path, data.as_bytes()).await?;
+            written.push(path);
+        }
+
+        Ok(written)
+    }
+}
+
+impl Row for Letter {
+    const HEADER: &'static str = "letter,unlocked,wpm_mean,wpm_harmonic_mean,clean";
+
+    fn fields(&self) -> Vec<String> {
+        vec![
+            self.letter.to_string(),
+            self.unlocked.to_string(),
+            self.wpm_mean.to_string(),
+            self.wpm_harmonic_mean.to_string(),
+            self.clean.to_string(),
+        ]
+    }
+}
+
+impl Row for Unlock {
+    const HEADER: &'static str = "letter,time,manual";
+
+    fn fields(&self) -> Vec<String> {
+        vec![
+            self.letter.to_string(),
+            self.time.clone(),
+            self.manual.to_string(),
+        ]
+    }
+}
+
+impl Row for Line {
+    const HEADER: &'static str = "time,duration_seconds,characters,errors,wpm,accuracy,unlocked";
+
+    fn fields(&self) -> Vec<String> {
+        vec![
+            self.time.clone(),
+            self.duration_seconds.to_string(),
+            self.characters.to_string(),
+            self.errors.to_string(),
+            self.wpm.to_string(),
+            self.accuracy.to_string(),
+            self.unlocked.to_string(),
+        ]
+    }
+}
+
+fn csv<R: Row>(rows: &[R]) -> String {
+    let mut out = String::from(R::HEADER);
+    out.push('\n');
+    for row in rows {
+        out.push_str(&row.fields().iter().map(|field| quote(field)).join(","));
+        out.push('\n');
+    }
+    out
+}
+
+/// Quote a field containing a separator, quote or line break, as in RFC 4180
+fn quote(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn timestamp(time: OffsetDateTime) -> String {
+    time.format(Format::Rfc3339)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn letter(letter: char) -> Letter {
+        Letter {
+            letter,
+            unlocked: true,
+            wpm_mean: 40.0,
+            wpm_harmonic_mean: 30.0,
+            clean: 0.5,
+        }
+    }
+
+    #[test]
+    fn separators_and_quotes_are_quoted() {
+        let rows = [letter('a'), letter(','), letter('"')];
+
+        assert_eq!(
+            csv(&rows),
+            "letter,unlocked,wpm_mean,wpm_harmonic_mean,clean\n\
+             a,true,40,30,0.5\n\
+             \",\",true,40,30,0.5\n\
+             \"\"\"\",true,40,30,0.5\n"
+        );
+    }
+
+    #[test]
+    fn files_stay_in_the_directory() {
+        let directory = std::env::temp_dir().join(format!("linkage-{}-export", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let statistics = Statistics {
+            profile: Name::parse("../a/b\\c: d").unwrap(),
+            exported: timestamp(OffsetDateTime::unix_epoch()),
+            letters: vec![letter('a')],
+            unlocks: Vec::new(),
+            history: Vec::new(),
+        };
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let written = runtime.block_on(statistics.write(&directory));
+        let left: Vec<_> = std::fs::read_dir(&directory)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .sorted()
+            .collect();
+        std::fs::remove_dir_all(&directory).unwrap();
+
+        assert_eq!(written.map(|paths| paths.len()).ok(), Some(4));
+        assert_eq!(
+            left,
+            [
+                "_._a_b_c_ d-history.csv",
+                "_._a_b_c_ d-letters.csv",
+                "_._a_b_c_ d-unlocks.csv",
+                "_._a_b_c_ d.json",
+            ]
+        );
+    }
+}
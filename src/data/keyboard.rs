@@ -1,7 +1,14 @@
+use super::language::Language;
 use super::CharSet;
+use itertools::Itertools;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
 
-#[derive(Debug, Default, Clone, Copy, Eq, PartialEq, Deserialize, Serialize)]
+/// Number of characters in the training set when a profile is created
+pub const INITIAL_CHARS: usize = 6;
+
+#[derive(Debug, Default, Clone, Eq, PartialEq, Deserialize, Serialize)]
 pub enum Layout {
     Colemak,
     ColemakDH,
@@ -14,6 +21,89 @@ pub enum Layout {
     QwertyOrtholinear,
     Workman,
     WorkmanOrtholinear,
+    /// User-defined layout loaded from a layout file
+    Custom(Custom),
+}
+
+/// Layout definition read from a JSON file in the `layouts` data directory:
+///
+/// ```json
+/// {
+///   "name": "Halmak",
+///   "geometry": "Staggered",
+///   "rows": ["wlrbz;qudj", "shnt,.aeoi", "fmvc/gpxky"],
+///   "fingers": ["0123366789", "0123366789", "0123366789"],
+///   "order": "ntaeoshilrudcmfgpwbvkyjxqz"
+/// }
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize)]
+pub struct Custom {
+    /// Name shown when picking a layout
+    pub name: String,
+    /// Physical arrangement of the keys
+    #[serde(default)]
+    pub geometry: Geometry,
+    /// Keys on each row, from the top row down
+    pub rows: Vec<String>,
+    /// Finger pressing each key in `rows`, numbered `0` (left pinky) through
+    /// `4` (left thumb) and `5` (right thumb) through `9` (right pinky)
+    pub fingers: Vec<String>,
+    /// The order in which lowercase letters are unlocked
+    pub order: String,
+}
+
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq, Deserialize, Serialize)]
+pub enum Geometry {
+    /// Rows offset from one another, as on most keyboards
+    #[default]
+    Staggered,
+    /// Keys aligned in a grid
+    Ortholinear,
+}
+
+/// Finger that presses a key, in order from left to right
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, Ord, PartialOrd)]
+pub enum Finger {
+    LeftPinky,
+    LeftRing,
+    LeftMiddle,
+    LeftIndex,
+    LeftThumb,
+    RightThumb,
+    RightIndex,
+    RightMiddle,
+    RightRing,
+    RightPinky,
+}
+
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq)]
+pub enum Hand {
+    Left,
+    Right,
+}
+
+/// Finger pressing each character of a layout
+pub type Fingers = HashMap<char, Finger>;
+
+pub const FINGERS: &[Finger] = &[
+    Finger::LeftPinky,
+    Finger::LeftRing,
+    Finger::LeftMiddle,
+    Finger::LeftIndex,
+    Finger::LeftThumb,
+    Finger::RightThumb,
+    Finger::RightIndex,
+    Finger::RightMiddle,
+    Finger::RightRing,
+    Finger::RightPinky,
+];
+
+#[derive(Debug)]
+pub enum Error {
+    FileSystem(std::io::Error),
+    Serde(serde_json::Error),
+    /// The layout file parsed, but does not describe a usable layout
+    Invalid(&'static str),
 }
 
 impl std::fmt::Display for Layout {
@@ -29,12 +119,43 @@ impl std::fmt::Display for Layout {
             Layout::QwertyOrtholinear => "Qwerty Ortholinear",
             Layout::Workman => "Workman Staggered",
             Layout::WorkmanOrtholinear => "Workman Ortholinear",
+            Layout::Custom(custom) => &custom.name,
         };
 
         write!(f, "{}", s)
     }
 }
 
+/// Punctuation unlocked once every capital letter is in the training set
+const PUNCTUATION: &[char] = &['.', ',', '\'', '-', '?', '!', ';', ':'];
+
+/// Number row, unlocked last, working outward from the index fingers
+const DIGITS: &[char] = &['4', '7', '5', '6', '3', '8', '2', '9', '1', '0'];
+
+/// Touch typing fingers for the rows of the built-in layouts, numbered as in
+/// layout files
+const STANDARD_FINGERS: &[&str] = &["0123366789", "0123366789", "01233667899", "0123366789"];
+
+/// Shifted symbols and the key they are typed with
+const SHIFTED: &[(char, char)] = &[
+    ('!', '1'),
+    ('@', '2'),
+    ('#', '3'),
+    ('$', '4'),
+    ('%', '5'),
+    ('^', '6'),
+    ('&', '7'),
+    ('*', '8'),
+    ('(', '9'),
+    (')', '0'),
+    ('_', '-'),
+    (':', ';'),
+    ('"', '\''),
+    ('<', ','),
+    ('>', '.'),
+    ('?', '/'),
+];
+
 pub const ALL: &[Layout] = &[
     Layout::Qwerty,
     Layout::Dvorak,
@@ -49,21 +170,122 @@ pub const ALL: &[Layout] = &[
 ];
 
 impl Layout {
-    pub fn initial_chars(&self) -> Vec<char> {
-        self.letter_order()[0..6].to_vec()
+    pub fn initial_chars(&self, language: Language) -> Vec<char> {
+        self.letter_order(language)[0..INITIAL_CHARS].to_vec()
     }
 
-    /// Get the next character in the list, given the current character set
-    pub fn next_char(&self, char_set: &CharSet) -> Option<char> {
-        self.letter_order()
+    /// The order in which characters are unlocked for this layout: lowercase
+    /// letters and any extra letters of the language, then capitals in the
+    /// same order, then punctuation and digits
+    pub fn letter_order(&self, language: Language) -> Vec<char> {
+        let lowercase: Vec<char> = self
+            .lowercase_order()
+            .into_iter()
+            .chain(language.extra_letters().iter().cloned())
+            .unique()
+            .collect();
+        let capitals: Vec<char> = lowercase
             .iter()
-            .filter(|&letter| !char_set.contains(letter))
-            .cloned()
-            .next()
+            .filter(|letter| letter.is_lowercase())
+            .flat_map(|letter| letter.to_uppercase())
+            .collect();
+
+        lowercase
+            .into_iter()
+            .chain(capitals)
+            .chain(PUNCTUATION.iter().cloned())
+            .chain(DIGITS.iter().cloned())
+            .unique()
+            .collect()
     }
 
-    /// The order in which letters are unlocked for this layout
-    pub fn letter_order(&self) -> Vec<char> {
+    /// Physical arrangement of the keys
+    pub fn geometry(&self) -> Geometry {
+        match self {
+            Layout::Colemak
+            | Layout::ColemakDH
+            | Layout::Dvorak
+            | Layout::Qwerty
+            | Layout::Workman => Geometry::Staggered,
+            Layout::ColemakDHOrtholinear
+            | Layout::ColemakOrtholinear
+            | Layout::DvorakOrtholinear
+            | Layout::QwertyOrtholinear
+            | Layout::WorkmanOrtholinear => Geometry::Ortholinear,
+            Layout::Custom(custom) => custom.geometry,
+        }
+    }
+
+    /// Unshifted characters on each row of keys, from the top row down
+    pub fn rows(&self) -> Vec<Vec<char>> {
+        let rows: &[&str] = match self {
+            Layout::Colemak | Layout::ColemakOrtholinear => {
+                &["1234567890", "qwfpgjluy;", "arstdhneio'", "zxcvbkm,./"]
+            }
+            Layout::ColemakDH | Layout::ColemakDHOrtholinear => {
+                &["1234567890", "qwfpbjluy;", "arstgmneio'", "zxcdvkh,./"]
+            }
+            Layout::Dvorak | Layout::DvorakOrtholinear => {
+                &["1234567890", "',.pyfgcrl", "aoeuidhtns-", ";qjkxbmwvz"]
+            }
+            Layout::Qwerty | Layout::QwertyOrtholinear => {
+                &["1234567890", "qwertyuiop", "asdfghjkl;'", "zxcvbnm,./"]
+            }
+            Layout::Workman | Layout::WorkmanOrtholinear => {
+                &["1234567890", "qdrwbjfup;", "ashtgyneoi'", "zxmcvkl,./"]
+            }
+            Layout::Custom(custom) => {
+                return custom
+                    .rows
+                    .iter()
+                    .map(|row| row.chars().collect())
+                    .collect();
+            }
+        };
+
+        rows.iter().map(|row| row.chars().collect()).collect()
+    }
+
+    /// Finger pressing each character, including capitals and shifted
+    /// symbols typed on the same key
+    pub fn fingers(&self) -> Fingers {
+        let fingers: Vec<String> = match self {
+            Layout::Custom(custom) => custom.fingers.clone(),
+            _ => STANDARD_FINGERS.iter().map(|row| row.to_string()).collect(),
+        };
+
+        let mut map: Fingers = self
+            .rows()
+            .into_iter()
+            .zip(fingers)
+            .flat_map(|(keys, fingers)| {
+                keys.into_iter()
+                    .zip(fingers.chars().filter_map(Finger::from_digit).collect_vec())
+            })
+            .collect();
+        let shifted: Vec<(char, Finger)> = map
+            .iter()
+            .flat_map(|(&key, &finger)| {
+                key.to_uppercase()
+                    .filter(move |&upper| upper != key)
+                    .chain(
+                        SHIFTED
+                            .iter()
+                            .filter(move |(_, base)| *base == key)
+                            .map(|(symbol, _)| *symbol),
+                    )
+                    .map(move |c| (c, finger))
+            })
+            .collect();
+        for (c, finger) in shifted {
+            map.entry(c).or_insert(finger);
+        }
+
+        map
+    }
+
+    /// The order in which lowercase letters are unlocked for this layout
+    fn lowercase_order(&self) -> Vec<char> {
         match self {
             Layout::Colemak => vec![
                 'n', 't', 'e', 's', 'i', 'r', 'o', 'a', 'l', 'p', 'u', 'f', 'y', 'w', 'm', 'v',
@@ -105,6 +327,135 @@ impl Layout {
                 'n', 't', 'e', 'h', 'o', 's', 'i', 'a', 'f', 'w', 'u', 'r', 'p', 'd', 'l', 'c',
                 'm', 'y', 'g', 'j', 'b', 'k', 'v', 'x', 'q', 'z',
             ],
+            Layout::Custom(custom) => custom.order.chars().collect(),
         }
     }
 }
+
+impl Finger {
+    /// Finger numbered as in layout files, from `0` (left pinky) through `9`
+    /// (right pinky)
+    pub fn from_digit(digit: char) -> Option<Self> {
+        FINGERS.get(digit.to_digit(10)? as usize).cloned()
+    }
+
+    pub fn hand(&self) -> Hand {
+        match self {
+            Finger::LeftPinky
+            | Finger::LeftRing
+            | Finger::LeftMiddle
+            | Finger::LeftIndex
+            | Finger::LeftThumb => Hand::Left,
+            _ => Hand::Right,
+        }
+    }
+}
+
+impl std::fmt::Display for Finger {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Finger::LeftPinky => "Left Pinky",
+            Finger::LeftRing => "Left Ring",
+            Finger::LeftMiddle => "Left Middle",
+            Finger::LeftIndex => "Left Index",
+            Finger::LeftThumb => "Left Thumb",
+            Finger::RightThumb => "Right Thumb",
+            Finger::RightIndex => "Right Index",
+            Finger::RightMiddle => "Right Middle",
+            Finger::RightRing => "Right Ring",
+            Finger::RightPinky => "Right Pinky",
+        };
+
+        write!(f, "{}", s)
+    }
+}
+
+impl std::fmt::Display for Hand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Hand::Left => "Left",
+            Hand::Right => "Right",
+        };
+
+        write!(f, "{}", s)
+    }
+}
+
+impl Custom {
+    pub fn parse(s: &str) -> Result<Self, Error> {
+        let custom: Custom = serde_json::from_str(s).map_err(Error::Serde)?;
+        custom.validate()?;
+        Ok(custom)
+    }
+
+    fn validate(&self) -> Result<(), Error> {
+        if self.name.trim().is_empty() {
+            return Err(Error::Invalid("missing name"));
+        }
+        if self.rows.len() != self.fingers.len() {
+            return Err(Error::Invalid("rows and fingers differ in length"));
+        }
+        for (row, fingers) in self.rows.iter().zip(self.fingers.iter()) {
+            if row.chars().count() != fingers.chars().count() {
+                return Err(Error::Invalid("row and fingers differ in length"));
+            }
+            if !fingers.chars().all(|c| c.is_ascii_digit()) {
+                return Err(Error::Invalid("fingers must be digits 0 through 9"));
+            }
+        }
+
+        let keys: CharSet = self.rows.iter().flat_map(|row| row.chars()).collect();
+        let order: Vec<char> = self.order.chars().collect();
+        if order.len() < INITIAL_CHARS {
+            return Err(Error::Invalid("unlock order is too short"));
+        }
+        if order.iter().unique().count() != order.len() {
+            return Err(Error::Invalid("unlock order repeats a character"));
+        }
+        if !order.iter().all(|c| keys.contains(c)) {
+            return Err(Error::Invalid(
+                "unlock order contains a character not on any key",
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// Load every valid layout file from the `layouts` data directory. Files that
+/// cannot be read are reported and skipped.
+pub async fn load_custom() -> Vec<Layout> {
+    let mut layouts = Vec::new();
+    let Some(dir) = path().await else {
+        return layouts;
+    };
+    let Ok(mut entries) = tokio::fs::read_dir(&dir).await else {
+        return layouts;
+    };
+
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let path = entry.path();
+        if path.extension().is_none_or(|ext| ext != "json") {
+            continue;
+        }
+        match load_file(&path).await {
+            Ok(custom) => layouts.push(Layout::Custom(custom)),
+            Err(error) => eprintln!("Could not load layout {}: {:?}", path.display(), error),
+        }
+    }
+
+    layouts.sort_by_key(|layout| layout.to_string());
+    layouts
+}
+
+async fn load_file(path: &std::path::Path) -> Result<Custom, Error> {
+    let data = tokio::fs::read(path).await.map_err(Error::FileSystem)?;
+    Custom::parse(&String::from_utf8_lossy(&data))
+}
+
+async fn path() -> Option<PathBuf> {
+    let mut path = super::dir().await?;
+    path.push("layouts");
+    tokio::fs::create_dir_all(&path).await.ok()?;
+    Some(path)
+}
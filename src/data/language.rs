@@ -0,0 +1,44 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq, Deserialize, Serialize)]
+pub enum Language {
+    #[default]
+    English,
+    French,
+    German,
+    Spanish,
+}
+
+pub const ALL: &[Language] = &[
+    Language::English,
+    Language::French,
+    Language::German,
+    Language::Spanish,
+];
+
+impl Language {
+    /// Letters beyond a through z, in the order they are unlocked
+    pub fn extra_letters(&self) -> &'static [char] {
+        match self {
+            Language::English => &[],
+            Language::French => &[
+                'é', 'è', 'à', 'ç', 'ê', 'â', 'ô', 'î', 'û', 'ù', 'ë', 'ï', 'œ',
+            ],
+            Language::German => &['ü', 'ä', 'ö', 'ß'],
+            Language::Spanish => &['ñ', 'á', 'é', 'í', 'ó', 'ú', 'ü'],
+        }
+    }
+}
+
+impl std::fmt::Display for Language {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Language::English => "English",
+            Language::French => "French",
+            Language::German => "German",
+            Language::Spanish => "Spanish",
+        };
+
+        write!(f, "{}", s)
+    }
+}
@@ -0,0 +1,386 @@
+use super::decoration;
+use super::dictionary::Dictionary;
+use super::language::Language;
+use super::CharSet;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// Marks the start of a word in a context
+const START: char = '\u{2}';
+/// Follows the last letter of a word
+const END: char = '\u{3}';
+/// Words generated for each one kept, in search of one that ends where
+/// corpus words end
+const ATTEMPTS: usize = 8;
+/// Lengths used while the corpus has no words of an allowed length
+const FALLBACK_LENGTHS: (usize, usize) = (3, 7);
+
+/// Letters of context the next letter can be chosen from
+pub const ORDERS: &[u8] = &[1, 2, 3, 4, 5];
+
+pub const WORD_LENGTHS: &[WordLengths] = &[
+    WordLengths::Corpus,
+    WordLengths::Range(2, 4),
+    WordLengths::Range(3, 7),
+    WordLengths::Range(5, 9),
+];
+
+/// How to build a model of pseudo-words
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Setting {
+    /// Text to learn from, in place of the language's word list
+    pub corpus: Option<PathBuf>,
+    /// Letters of context each letter is chosen from
+    pub order: u8,
+    pub lengths: WordLengths,
+}
+
+/// How long pseudo-words are
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum WordLengths {
+    /// Each length as often as it appears in the corpus
+    Corpus,
+    /// Lengths from the first to the second number of letters, weighted as
+    /// in the corpus
+    Range(u8, u8),
+}
+
+/// Character model of a corpus, which makes up words that look like the
+/// words it was trained on. Each letter is chosen from those that followed
+/// the last `order` letters in the corpus, backing off to shorter contexts
+/// when none of those letters is in the character set.
+#[derive(Debug, Clone)]
+pub struct Markov {
+    order: usize,
+    /// Letters seen after each context of up to `order` characters, with how
+    /// often, in letter order. Contexts at the start of a word begin with
+    /// `START`.
+    next: HashMap<String, Vec<(char, u32)>>,
+    /// Number of corpus words of each length
+    lengths: BTreeMap<usize, u32>,
+}
+
+/// What a model is trained from
+type Key = (Language, Option<PathBuf>, u8);
+
+impl Markov {
+    /// The model for the setting and language, trained the first time it is
+    /// asked for and shared after that. Training reads the corpus and counts
+    /// every word, too slow to repeat each time the letters change.
+    pub fn cached(setting: &Setting, language: Language) -> Arc<Self> {
+        static MODELS: Mutex<Vec<(Key, Arc<Markov>)>> = Mutex::new(Vec::new());
+
+        let key = (language, setting.corpus.clone(), setting.order);
+        let mut models = MODELS.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some((_, model)) = models.iter().find(|(k, _)| *k == key) {
+            return model.clone();
+        }
+
+        let model = Arc::new(Self::load(setting, language));
+        models.push((key, model.clone()));
+        model
+    }
+
+    /// Train on the setting's corpus, falling back to the language's word
+    /// list while the corpus cannot be read or contains no words
+    pub fn load(setting: &Setting, language: Language) -> Self {
+        setting
+            .corpus
+            .as_ref()
+            .and_then(|path| match std::fs::read_to_string(path) {
+                Ok(text) => Some(text),
+                Err(error) => {
+                    eprintln!("Could not read words from {}: {}", path.display(), error);
+                    None
+                }
+            })
+            .map(|text| {
+                let words = corpus_words(&text);
+                Self::train(words.iter().map(String::as_str), setting.order)
+            })
+            .filter(|model| !model.is_empty())
+            .unwrap_or_else(|| {
+                let dictionary = Dictionary::load(language);
+                Self::train(dictionary.words().iter().map(String::as_str), setting.order)
+            })
+    }
+
+    /// Count the letters following every context of up to `order` letters
+    /// in the words
+    pub fn train<'a>(words: impl IntoIterator<Item = &'a str>, order: u8) -> Self {
+        let order = order.clamp(ORDERS[0], ORDERS[ORDERS.len() - 1]) as usize;
+        let mut counts: HashMap<String, BTreeMap<char, u32>> = HashMap::new();
+        let mut lengths = BTreeMap::new();
+
+        for word in words {
+            let chars: Vec<char> = std::iter::once(START)
+                .chain(word.chars())
+                .chain(std::iter::once(END))
+                .collect();
+            if chars.len() == 2 {
+                continue;
+            }
+            *lengths.entry(chars.len() - 2).or_insert(0) += 1;
+
+            for i in 1..chars.len() {
+                for k in 0..=order.min(i) {
+                    let context: String = chars[i - k..i].iter().collect();
+                    *counts
+                        .entry(context)
+                        .or_default()
+                        .entry(chars[i])
+                        .or_insert(0) += 1;
+                }
+            }
+        }
+
+        Self {
+            order,
+            next: counts
+                .into_iter()
+                .map(|(context, next)| (context, next.into_iter().collect()))
+                .collect(),
+            lengths,
+        }
+    }
+
+    /// Whether the model was trained on no words at all
+    pub fn is_empty(&self) -> bool {
+        self.lengths.is_empty()
+    }
+
+    /// Whether any letter the corpus uses is in the set, so that words can
+    /// be made up from it
+    pub fn fits(&self, char_set: &CharSet) -> bool {
+        self.next
+            .get("")
+            .is_some_and(|next| next.iter().any(|(c, _)| char_set.contains(c)))
+    }
+
+    pub fn line<R: Rng>(
+        &self,
+        char_set: &CharSet,
+        lengths: WordLengths,
+        min_length: usize,
+        focus: &CharSet,
+        rng: &mut R,
+    ) -> String {
+        let mut line = String::new();
+        let mut sentence_start = true;
+        while line.chars().count() < min_length {
+            let word = self.word(char_set, lengths, rng);
+            // None of the corpus letters are in the character set
+            if word.is_empty() {
+                break;
+            }
+            let word = decoration::decorate(&word, sentence_start, char_set, focus, rng);
+            decoration::push_word(&mut line, &word);
+            sentence_start = decoration::ends_sentence(&word);
+        }
+        line
+    }
+
+    /// Make up a word from letters in the character set. Words that stop
+    /// where a corpus word could end are preferred.
+    pub fn word<R: Rng>(&self, char_set: &CharSet, lengths: WordLengths, rng: &mut R) -> String {
+        let length = self.length(lengths, rng);
+        let mut word = String::new();
+        for _ in 0..ATTEMPTS {
+            let (attempt, ends) = self.attempt(char_set, length, rng);
+            word = attempt;
+            if ends {
+                break;
+            }
+        }
+        word
+    }
+
+    /// A word of up to `length` letters, and whether it ends naturally
+    fn attempt<R: Rng>(&self, char_set: &CharSet, length: usize, rng: &mut R) -> (String, bool) {
+        let mut context = vec![START];
+        while context.len() <= length {
+            match self.sample(&context, char_set, rng) {
+                Some(letter) => context.push(letter),
+                None => break,
+            }
+        }
+
+        let ends = self
+            .longest(&context)
+            .is_some_and(|next| next.iter().any(|&(c, _)| c == END));
+        (context[1..].iter().collect(), ends)
+    }
+
+    /// Choose a letter in the character set to follow the context, from the
+    /// longest part of it that has one
+    fn sample<R: Rng>(&self, context: &[char], char_set: &CharSet, rng: &mut R) -> Option<char> {
+        (0..=context.len().min(self.order)).rev().find_map(|k| {
+            let key: String = context[context.len() - k..].iter().collect();
+            let next: Vec<(char, u32)> = self
+                .next
+                .get(&key)?
+                .iter()
+                .filter(|(c, _)| *c != END && char_set.contains(c))
+                .cloned()
+                .collect();
+            let total: u32 = next.iter().map(|(_, count)| count).sum();
+            if total == 0 {
+                return None;
+            }
+
+            let mut value = rng.gen_range(0..total);
+            next.into_iter()
+                .find(|&(_, count)| {
+                    if value < count {
+                        true
+                    } else {
+                        value -= count;
+                        false
+                    }
+                })
+                .map(|(c, _)| c)
+        })
+    }
+
+    /// What follows the longest part of the context seen in the corpus
+    fn longest(&self, context: &[char]) -> Option<&Vec<(char, u32)>> {
+        (1..=context.len().min(self.order)).rev().find_map(|k| {
+            self.next
+                .get(&context[context.len() - k..].iter().collect::<String>())
+        })
+    }
+
+    /// Choose a word length, as often as each allowed length appears in the
+    /// corpus
+    fn length<R: Rng>(&self, lengths: WordLengths, rng: &mut R) -> usize {
+        let (min, max) = match lengths {
+            WordLengths::Corpus => (1, usize::MAX),
+            WordLengths::Range(min, max) => (min.max(1) as usize, max.max(min) as usize),
+        };
+        let weights: Vec<(usize, u32)> = self
+            .lengths
+            .range(min..=max)
+            .map(|(&length, &count)| (length, count))
+            .collect();
+        let total: u32 = weights.iter().map(|(_, count)| count).sum();
+        if total == 0 {
+            let (min, max) = match lengths {
+                WordLengths::Corpus => FALLBACK_LENGTHS,
+                WordLengths::Range(..) => (min, max),
+            };
+            return rng.gen_range(min..=max);
+        }
+
+        let mut value = rng.gen_range(0..total);
+        for (length, count) in weights {
+            if value < count {
+                return length;
+            }
+            value -= count;
+        }
+        max
+    }
+}
+
+/// Every word of letters in the text, lowercased and without surrounding
+/// punctuation. Words are kept as often as they appear, so common ones weigh
+/// more.
+fn corpus_words(text: &str) -> Vec<String> {
+    text.split_whitespace()
+        .map(|token| {
+            token
+                .trim_matches(|c: char| !c.is_alphabetic())
+                .to_lowercase()
+        })
+        .filter(|word| !word.is_empty() && word.chars().all(char::is_alphabetic))
+        .collect()
+}
+
+impl Default for Setting {
+    fn default() -> Self {
+        Self {
+            corpus: None,
+            order: 3,
+            lengths: WordLengths::Range(3, 7),
+        }
+    }
+}
+
+impl std::fmt::Display for WordLengths {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WordLengths::Corpus => write!(f, "As in the corpus"),
+            WordLengths::Range(min, max) => write!(f, "{} to {} letters", min, max),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn empty_corpus_falls_back_to_the_language() {
+        let path = std::env::temp_dir().join(format!("linkage-{}-corpus.txt", std::process::id()));
+        std::fs::write(&path, "").unwrap();
+        let setting = Setting {
+            corpus: Some(path.clone()),
+            ..Setting::default()
+        };
+        let model = Markov::load(&setting, Language::English);
+        std::fs::remove_file(path).unwrap();
+
+        assert!(!model.is_empty());
+    }
+
+    #[test]
+    fn models_are_trained_once() {
+        let setting = Setting {
+            order: 2,
+            ..Setting::default()
+        };
+
+        assert!(Arc::ptr_eq(
+            &Markov::cached(&setting, Language::Spanish),
+            &Markov::cached(&setting, Language::Spanish)
+        ));
+    }
+
+    #[test]
+    fn words_use_only_the_character_set() {
+        let model = Markov::train(["banana", "bandana", "cabana", "nab"], 3);
+        let char_set: CharSet = "abn".chars().collect();
+        let mut rng = StdRng::seed_from_u64(1);
+
+        for _ in 0..100 {
+            let word = model.word(&char_set, WordLengths::Range(3, 7), &mut rng);
+            assert!(!word.is_empty());
+            assert!(word.chars().all(|c| char_set.contains(&c)), "{}", word);
+        }
+    }
+
+    #[test]
+    fn words_start_as_corpus_words_do() {
+        let model = Markov::train(["banana", "bandana", "band"], 2);
+        let char_set: CharSet = "abdn".chars().collect();
+        let mut rng = StdRng::seed_from_u64(1);
+
+        for _ in 0..100 {
+            let word = model.word(&char_set, WordLengths::Corpus, &mut rng);
+            assert!(word.starts_with("ba"), "{}", word);
+        }
+    }
+
+    #[test]
+    fn corpus_words_keep_repeats_and_skip_numbers() {
+        assert_eq!(
+            corpus_words("The cat, the hat. 2024 (the) b4d"),
+            ["the", "cat", "the", "hat", "the"]
+        );
+    }
+}
@@ -1,9 +1,15 @@
-use super::keyboard::Layout;
-use super::training::{Difficulty, Line, Session, State};
+use super::keyboard::{Layout, INITIAL_CHARS};
+use super::language::Language;
+use super::recording::Ghost;
+use super::training::{Difficulty, Line, Session, State, Targets};
+use super::typing_test::Score;
 use super::words;
 use super::zipper_list::{Item, ZipperList};
+use super::Error;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::collections::HashSet;
+use std::path::Path;
 
 #[derive(Debug, Clone, Hash, Eq, PartialEq, PartialOrd, Deserialize, Serialize)]
 pub struct Name(String);
@@ -12,18 +18,40 @@ pub struct Name(String);
 pub struct Profile {
     name: Name,
     layout: Layout,
+    #[serde(default)]
+    language: Language,
     difficulty: Difficulty,
+    /// Targets used when the difficulty is custom
+    #[serde(default)]
+    custom: Targets,
     state: State,
     words: words::Setting,
+    /// Seed every session starts from, instead of a new one each time
+    #[serde(default)]
+    seed: Option<u64>,
+    #[serde(default)]
+    ghost: Ghost,
+    /// Results of typing tests, kept apart from training statistics
+    #[serde(default)]
+    tests: Vec<Score>,
 }
 
 #[derive(Debug, Clone)]
 pub struct Active {
     pub name: Name,
     pub layout: Layout,
+    pub language: Language,
     pub difficulty: Difficulty,
+    /// Targets used when the difficulty is custom
+    pub custom: Targets,
     pub state: State,
     pub session: Session,
+    /// Seed every session starts from, instead of a new one each time
+    pub seed: Option<u64>,
+    /// Pace to race against while training
+    pub ghost: Ghost,
+    /// Results of typing tests, kept apart from training statistics
+    pub tests: Vec<Score>,
 }
 
 #[derive(Debug, Clone)]
@@ -31,6 +59,22 @@ pub struct List {
     zipper: ZipperList<Profile, Active>,
 }
 
+/// Why a letter could not be taken out of the training set
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LockError {
+    /// The set is already as small as a new profile's
+    TooFewLetters,
+    /// No words would be left made only of the other letters
+    NoWords,
+}
+
+/// A single profile in a file of its own, for moving between machines
+#[derive(Debug, Serialize)]
+struct Exported {
+    version: u16,
+    profile: Profile,
+}
+
 #[derive(Debug, Clone, Default, Deserialize, Serialize)]
 pub struct Saved {
     prev: Vec<Profile>,
@@ -39,41 +83,218 @@ pub struct Saved {
 }
 
 impl Profile {
-    pub fn new(name: Name, layout: Layout, difficulty: Difficulty) -> Self {
-        let chars = layout.initial_chars();
+    pub fn new(name: Name, layout: Layout, language: Language, difficulty: Difficulty) -> Self {
+        let chars = layout.initial_chars(language);
         let state = State::new(chars);
 
         Self {
             name,
             layout,
+            language,
             difficulty,
+            custom: Targets::default(),
             state,
             words: words::Setting::default(),
+            seed: None,
+            ghost: Ghost::default(),
+            tests: Vec::new(),
         }
     }
+
+    pub fn name(&self) -> &Name {
+        &self.name
+    }
+
+    /// Write the profile to a standalone file
+    pub async fn export(&self, path: &Path) -> Result<(), Error> {
+        let exported = Exported {
+            version: super::VERSION,
+            profile: self.clone(),
+        };
+        let data = serde_json::to_string_pretty(&exported).map_err(Error::Serde)?;
+
+        super::write_atomic(path, data.as_bytes()).await
+    }
+
+    /// Read a profile written by `export`, upgrading it from earlier
+    /// versions
+    pub async fn import(path: &Path) -> Result<Profile, Error> {
+        let data = tokio::fs::read(path).await.map_err(Error::FileSystem)?;
+        let data = String::from_utf8_lossy(&data);
+        let exported: Value = serde_json::from_str(&data).map_err(Error::Serde)?;
+
+        Self::from_exported(exported)
+    }
+
+    /// The profile in an exported file. It is upgraded as the current
+    /// profile of a save file, so it goes through the same migrations.
+    fn from_exported(mut exported: Value) -> Result<Profile, Error> {
+        let version = exported.get("version").cloned().ok_or(Error::Corrupted)?;
+        let profile = exported
+            .get_mut("profile")
+            .map(Value::take)
+            .ok_or(Error::Corrupted)?;
+        let mut saved = serde_json::json!({
+            "version": version,
+            "profiles": { "prev": [], "current": profile, "next": [] },
+        });
+        super::migrate(&mut saved)?;
+
+        let profile: Profile =
+            serde_json::from_value(saved["profiles"]["current"].take()).map_err(Error::Serde)?;
+        if Name::parse(&profile.name.0).is_none() {
+            return Err(Error::InvalidName(profile.name.0));
+        }
+
+        Ok(profile)
+    }
 }
 
 impl Default for Profile {
     fn default() -> Self {
         let layout = Layout::default();
-        let chars = layout.initial_chars();
+        let language = Language::default();
+        let chars = layout.initial_chars(language);
         let state = State::new(chars);
 
         Self {
             name: Name::unchecked_from("Default Profile"),
             layout,
+            language,
             difficulty: Difficulty::default(),
+            custom: Targets::default(),
             state,
             words: words::Setting::default(),
+            seed: None,
+            ghost: Ghost::default(),
+            tests: Vec::new(),
         }
     }
 }
 
 impl Active {
     pub fn add_line(&mut self, line: Line) -> Option<words::Words> {
-        self.state
-            .add_line(line, &self.layout, &self.difficulty)
-            .map(|char_set| self.session.words_setting().get_words(char_set))
+        let targets = self.targets();
+        let words = self
+            .state
+            .add_line(line, &self.layout, self.language, &targets)
+            .map(|char_set| {
+                self.session
+                    .words_setting()
+                    .get_words(char_set, self.language)
+            });
+
+        // Once there is nothing left to unlock, keep pushing the speed up
+        if self.difficulty == Difficulty::Custom
+            && self.custom.auto_raise
+            && self
+                .state
+                .unlock_order(&self.layout, self.language)
+                .is_empty()
+            && self.state.meets(&self.custom)
+        {
+            self.custom = self.custom.raised();
+        }
+
+        words
+    }
+
+    /// Speed and accuracy letters must reach to unlock the next one
+    pub fn targets(&self) -> Targets {
+        self.difficulty.preset().unwrap_or(self.custom)
+    }
+
+    /// Add a letter to the training set by hand
+    pub fn unlock(&mut self, letter: char) {
+        let time = self.session.clock().now_utc();
+        if self.state.unlock(letter, time) {
+            self.update_char_set();
+        }
+    }
+
+    /// Add every letter still to unlock to the training set
+    pub fn unlock_all(&mut self) {
+        let time = self.session.clock().now_utc();
+        self.state.unlock_all(&self.layout, self.language, time);
+        self.update_char_set();
+    }
+
+    /// Take a letter out of the training set by hand, unless that would
+    /// leave no words made only of the letters left
+    pub fn lock(&mut self, letter: char) -> Result<(), LockError> {
+        let mut char_set = self.state.char_set();
+        if !char_set.remove(&letter) {
+            return Ok(());
+        }
+        if char_set.len() < INITIAL_CHARS {
+            return Err(LockError::TooFewLetters);
+        }
+        let words = self
+            .session
+            .words_setting()
+            .get_words(char_set.clone(), self.language);
+        if !words.fits(&char_set) {
+            return Err(LockError::NoWords);
+        }
+
+        let time = self.session.clock().now_utc();
+        if self.state.lock(letter, time) {
+            self.session.update_words(words);
+        }
+
+        Ok(())
+    }
+
+    /// Unlock these letters next, in this order
+    pub fn reorder(&mut self, first: &[char]) {
+        let time = self.session.clock().now_utc();
+        self.state.reorder(first, &self.layout, self.language, time);
+    }
+
+    /// Pick words for the training set after it changes by hand
+    fn update_char_set(&mut self) {
+        let words = self
+            .session
+            .words_setting()
+            .get_words(self.state.char_set(), self.language);
+        self.session.update_words(words);
+    }
+
+    /// Switch where words come from
+    pub fn set_words(&mut self, setting: words::Setting) {
+        let words = setting.get_words(self.state.char_set(), self.language);
+        self.session.update_words(words);
+    }
+
+    /// Switch language, picking words for the current character set from the
+    /// new language
+    pub fn set_language(&mut self, language: Language) {
+        self.language = language;
+        let words = self
+            .session
+            .words_setting()
+            .get_words(self.state.char_set(), language);
+        self.session.update_words(words);
+    }
+
+    /// Start the session over with lines generated from `seed`, keeping its
+    /// clock
+    pub fn replay(&mut self, seed: u64) {
+        self.session = Session::new(
+            &self.session.words_setting(),
+            &self.state,
+            &self.layout,
+            self.language,
+            seed,
+        )
+        .with_clock(self.session.clock().clone());
+    }
+
+    /// Start every session from the same seed, or from a new one each time
+    /// when `None`. The current session starts over from the seed.
+    pub fn set_seed(&mut self, seed: Option<u64>) {
+        self.seed = seed;
+        self.replay(seed.unwrap_or_else(rand::random));
     }
 }
 
@@ -116,6 +337,21 @@ impl List {
         self.zipper.select(len.saturating_sub(1));
     }
 
+    /// Add a profile from elsewhere and make it active, numbering its name
+    /// if another profile already has it
+    pub fn import(&mut self, mut profile: Profile) -> Name {
+        profile.name = Name::numbered(&profile.name.0, self);
+        let name = profile.name.clone();
+        self.insert_active(profile);
+
+        name
+    }
+
+    /// Copy of the active profile, as it would be saved
+    pub fn export_active(&self) -> Profile {
+        self.active().clone().into()
+    }
+
     /// Iterator of (name, is_active)
     pub fn names(&self) -> impl Iterator<Item = (Name, bool)> + '_ {
         self.iter().map(|item| match item {
@@ -144,13 +380,25 @@ impl Saved {
 
 impl From<Profile> for Active {
     fn from(profile: Profile) -> Self {
-        let session = Session::new(&profile.words, &profile.state);
+        let seed = profile.seed.unwrap_or_else(rand::random);
+        let session = Session::new(
+            &profile.words,
+            &profile.state,
+            &profile.layout,
+            profile.language,
+            seed,
+        );
         Self {
             name: profile.name,
             layout: profile.layout,
+            language: profile.language,
             difficulty: profile.difficulty,
+            custom: profile.custom,
             state: profile.state,
             session,
+            seed: profile.seed,
+            ghost: profile.ghost,
+            tests: profile.tests,
         }
     }
 }
@@ -160,9 +408,14 @@ impl From<Active> for Profile {
         Self {
             name: active.name,
             layout: active.layout,
+            language: active.language,
             difficulty: active.difficulty,
+            custom: active.custom,
             state: active.state,
             words: active.session.words_setting(),
+            seed: active.seed,
+            ghost: active.ghost,
+            tests: active.tests,
         }
     }
 }
@@ -198,23 +451,49 @@ impl Name {
     }
 
     pub fn new(list: &List) -> Name {
-        let base: String = "New Profile".to_string();
+        Self::numbered("New Profile", list)
+    }
+
+    /// The base name, followed by a number when needed to make it unique in
+    /// the list. The base is shortened if the number would not fit.
+    fn numbered(base: &str, list: &List) -> Name {
         let existing: HashSet<String> = list.names().map(|(n, _)| String::from(n)).collect();
 
-        if existing.contains(&base) {
+        if existing.contains(base) {
             let mut counter = 2;
             loop {
-                let name = format!("{} ({})", base, counter);
+                let suffix = format!(" ({})", counter);
+                let mut prefix = base.to_string();
+                while prefix.len() + suffix.len() > Self::MAX_LENGTH {
+                    prefix.pop();
+                }
+                let name = format!("{}{}", prefix.trim_end(), suffix);
                 if !existing.contains(&name) {
                     return Name(name);
                 }
                 counter += 1;
             }
         } else {
-            Name(base)
+            Name(base.to_string())
         }
     }
 
+    /// The name as a file name, with path separators and other characters
+    /// file systems refuse replaced
+    pub fn file_name(&self) -> String {
+        self.0
+            .chars()
+            .enumerate()
+            .map(|(i, c)| match c {
+                '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+                // A leading dot would hide the file, or name a directory
+                '.' if i == 0 => '_',
+                c if c.is_control() => '_',
+                c => c,
+            })
+            .collect()
+    }
+
     fn unchecked_from(s: &str) -> Name {
         Self(s.to_string())
     }
@@ -231,3 +510,90 @@ impl std::fmt::Display for Name {
         write!(f, "{}", self.0)
     }
 }
+
+impl std::fmt::Display for LockError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LockError::TooFewLetters => {
+                write!(f, "At least {} letters stay in training", INITIAL_CHARS)
+            }
+            LockError::NoWords => write!(f, "No words would be left to type"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn locking_keeps_words_to_type() {
+        let mut active = Active::from(Profile::default());
+        for letter in ['k', 'z', 'q', 'x', 'v'] {
+            active.unlock(letter);
+        }
+        let results: Vec<_> = ['a', 's', 'd', 'f', 'e']
+            .into_iter()
+            .map(|letter| active.lock(letter))
+            .collect();
+
+        assert!(results.contains(&Err(LockError::NoWords)), "{:?}", results);
+        let char_set = active.state.char_set();
+        let words = active
+            .session
+            .words_setting()
+            .get_words(char_set.clone(), active.language);
+        assert!(words.fits(&char_set));
+    }
+
+    #[test]
+    fn locking_keeps_the_initial_number_of_letters() {
+        let mut active = Active::from(Profile::default());
+
+        assert_eq!(active.lock('a'), Err(LockError::TooFewLetters));
+        assert!(active.state.char_set().contains(&'a'));
+    }
+
+    fn exported(version: u64, name: &str) -> Value {
+        let mut exported = serde_json::to_value(Exported {
+            version: crate::data::VERSION,
+            profile: Profile::default(),
+        })
+        .unwrap();
+        exported["version"] = version.into();
+        exported["profile"]["name"] = name.into();
+        exported
+    }
+
+    #[test]
+    fn import_checks_the_version() {
+        let version = crate::data::VERSION as u64;
+
+        let profile = Profile::from_exported(exported(version, "Mine")).unwrap();
+        assert_eq!(profile.name().to_string(), "Mine");
+        assert!(matches!(
+            Profile::from_exported(exported(version + 1, "Mine")),
+            Err(Error::Newer(_))
+        ));
+    }
+
+    #[test]
+    fn import_checks_the_name() {
+        let version = crate::data::VERSION as u64;
+        let long = "x".repeat(Name::MAX_LENGTH + 1);
+
+        for name in ["", " Mine", long.as_str()] {
+            assert!(matches!(
+                Profile::from_exported(exported(version, name)),
+                Err(Error::InvalidName(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn file_names_stay_in_the_directory() {
+        let name = Name::parse("../a/b\\c: d").unwrap();
+
+        assert_eq!(name.file_name(), "_._a_b_c_ d");
+    }
+}
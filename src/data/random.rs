@@ -1,22 +1,26 @@
+use super::decoration;
+use super::language::Language;
 use super::CharSet;
 use rand::distributions::{Distribution, Uniform};
-use rand::rngs::ThreadRng;
+use rand::Rng;
 use serde::Deserialize;
 use std::collections::HashMap;
 
 const SCALAR: u16 = 10000;
+const DE_FREQ: &[u8] = include_bytes!("../../data/de/freq.json");
 const EN_FREQ: &[u8] = include_bytes!("../../data/en/freq.json");
+const ES_FREQ: &[u8] = include_bytes!("../../data/es/freq.json");
+const FR_FREQ: &[u8] = include_bytes!("../../data/fr/freq.json");
 
 #[derive(Debug, Clone)]
 pub struct Random {
     letter: Vec<(u16, char)>,
     next_letter: HashMap<char, Vec<(u16, char)>>,
     dist: Uniform<u16>,
-    rng: ThreadRng,
 }
 
 impl Random {
-    pub fn load() -> Random {
+    pub fn load(language: Language) -> Random {
         #[derive(Debug, Deserialize)]
         struct FreqData {
             #[serde(rename = "letter_frequency")]
@@ -25,7 +29,13 @@ impl Random {
             next_letter: HashMap<String, HashMap<String, f32>>,
         }
 
-        let freq_str = std::str::from_utf8(EN_FREQ).expect("Loading frequency data");
+        let freq = match language {
+            Language::English => EN_FREQ,
+            Language::French => FR_FREQ,
+            Language::German => DE_FREQ,
+            Language::Spanish => ES_FREQ,
+        };
+        let freq_str = std::str::from_utf8(freq).expect("Loading frequency data");
         let freq_data: FreqData = serde_json::from_str(freq_str).expect("Parse frequency JSON");
 
         let next_letter: HashMap<char, Vec<(u16, char)>> = freq_data
@@ -40,30 +50,44 @@ impl Random {
             letter: cumulative(freq_data.letter),
             next_letter,
             dist: Uniform::new_inclusive(0, SCALAR),
-            rng: rand::thread_rng(),
         }
     }
 
-    pub fn line(&mut self, char_set: &CharSet, min_length: usize) -> String {
+    pub fn line<R: Rng>(
+        &self,
+        char_set: &CharSet,
+        min_length: usize,
+        focus: &CharSet,
+        rng: &mut R,
+    ) -> String {
         let mut line = String::new();
+        let mut sentence_start = true;
         while line.chars().count() < min_length {
-            let word = self.word(char_set);
-            line.push_str(&word);
-            line.push(' ');
+            let word = self.word(char_set, rng);
+            if word.is_empty() {
+                break;
+            }
+            let word = decoration::decorate(&word, sentence_start, char_set, focus, rng);
+            decoration::push_word(&mut line, &word);
+            sentence_start = decoration::ends_sentence(&word);
         }
         line
     }
 
     /// Generate a random word from this frequency distribution. Parameters are
-    /// word length and available character set.
-    pub fn word(&mut self, char_set: &CharSet) -> String {
-        let mut last_letter = self.first_letter(char_set);
+    /// word length and available character set. Every letter is in the set,
+    /// and the word is empty when the set has no letters.
+    pub fn word<R: Rng>(&self, char_set: &CharSet, rng: &mut R) -> String {
         let Random {
+            letter,
             next_letter,
             dist,
-            rng,
-            ..
         } = self;
+        // A space would end the word, so only letters are drawn
+        let letters: CharSet = char_set.iter().copied().filter(|&c| c != ' ').collect();
+        let Some(mut last_letter) = sample_cumulative(dist.sample(rng), letter, &letters) else {
+            return String::new();
+        };
 
         let target = (3 + dist.sample(rng) % 5) as usize;
 
@@ -71,34 +95,18 @@ impl Random {
         word.push(last_letter);
 
         while word.len() < target {
-            if let Some(freqs) = next_letter.get(&last_letter) {
-                let v = dist.sample(rng);
-                let test_letter = sample_cumulative(v, freqs, char_set);
-                if test_letter == ' ' {
-                    continue;
-                }
-                last_letter = test_letter;
-                word.push(last_letter);
-            } else {
+            let Some(next) = next_letter
+                .get(&last_letter)
+                .and_then(|freqs| sample_cumulative(dist.sample(rng), freqs, &letters))
+            else {
                 break;
-            }
+            };
+            last_letter = next;
+            word.push(last_letter);
         }
 
         word
     }
-
-    fn first_letter(&mut self, char_set: &CharSet) -> char {
-        let Random {
-            letter, dist, rng, ..
-        } = self;
-        loop {
-            let v = dist.sample(rng);
-            let l = sample_cumulative(v, letter, char_set);
-            if l != ' ' {
-                return l;
-            }
-        }
-    }
 }
 
 impl Default for Random {
@@ -107,7 +115,6 @@ impl Default for Random {
             letter: Vec::new(),
             next_letter: HashMap::new(),
             dist: Uniform::new_inclusive(0, SCALAR),
-            rng: rand::thread_rng(),
         }
     }
 }
@@ -122,7 +129,9 @@ fn cumulative(mapping: HashMap<String, f32>) -> Vec<(u16, char)> {
         .iter()
         .map(|(letter, ratio)| ((s32 * ratio).round() as u16, letter.chars().next().unwrap()))
         .collect();
-    cumulative.sort_by(|(a, _), (b, _)| b.cmp(a));
+    // Break ties by letter, so that the same seed gives the same words
+    // whatever order the map was read in
+    cumulative.sort_by(|(a, x), (b, y)| b.cmp(a).then(x.cmp(y)));
     let mut c = 0;
     cumulative
         .into_iter()
@@ -133,18 +142,20 @@ fn cumulative(mapping: HashMap<String, f32>) -> Vec<(u16, char)> {
         .collect()
 }
 
-fn sample_cumulative(value: u16, mapping: &[(u16, char)], char_set: &CharSet) -> char {
-    if let Some(c) = mapping
+/// Sample a letter in the set, or `None` when the mapping has none
+fn sample_cumulative(value: u16, mapping: &[(u16, char)], char_set: &CharSet) -> Option<char> {
+    let mut in_set = mapping
         .iter()
+        .copied()
         .filter(|(_, c)| char_set.contains(c))
-        .take_while(|(v, _)| v <= &value)
-        .map(|(_, c)| *c)
-        .last()
-    {
-        c
-    } else if !mapping.is_empty() {
-        mapping[0].1
-    } else {
-        'n'
-    }
+        .peekable();
+    let (_, first) = *in_set.peek()?;
+
+    Some(
+        in_set
+            .take_while(|(v, _)| *v <= value)
+            .map(|(_, c)| c)
+            .last()
+            .unwrap_or(first),
+    )
 }
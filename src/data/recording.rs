@@ -0,0 +1,250 @@
+use super::training::{WordsPerMinute, MAX_ERRORS};
+use serde::{Deserialize, Serialize};
+use time::{Duration, OffsetDateTime};
+
+/// Every key pressed while typing a line, for playing the line back
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Recording {
+    /// When the line was completed
+    pub time: OffsetDateTime,
+    /// The line of text that was typed
+    pub text: String,
+    pub strokes: Vec<Stroke>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Stroke {
+    /// Milliseconds since the first key of the line
+    pub at: u32,
+    pub key: Key,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Key {
+    Char(char),
+    Backspace,
+}
+
+/// Pace to race against while training, shown as a second cursor
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub enum Ghost {
+    #[default]
+    Off,
+    /// The fastest line typed so far
+    Best,
+    /// A line chosen from the recordings
+    Line(Recording),
+}
+
+/// The kind of ghost, without its recording
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GhostKind {
+    Off,
+    Best,
+    Line,
+}
+
+pub const GHOST_KINDS: &[GhostKind] = &[GhostKind::Off, GhostKind::Best, GhostKind::Line];
+
+/// How far through a recording the typist had got at some moment
+#[derive(Debug, Clone, Default)]
+pub struct Playback {
+    /// Whether each character typed so far was missed before it was hit
+    pub typed: Vec<bool>,
+    /// Incorrect keys waiting to be deleted
+    pub errors: Vec<char>,
+    /// Number of incorrect keys pressed
+    pub misses: usize,
+}
+
+impl Recording {
+    /// Time from the first key of the line to the last
+    pub fn duration(&self) -> Duration {
+        self.strokes
+            .last()
+            .map(|stroke| stroke.elapsed())
+            .unwrap_or_default()
+    }
+
+    /// Typing speed across the whole line. Timing starts at the first key,
+    /// so a line typed with a single key took no time and has no speed.
+    pub fn wpm(&self) -> Option<WordsPerMinute> {
+        let duration = self.duration();
+        if duration <= Duration::zero() {
+            return None;
+        }
+        let characters = self.text.chars().count().max(1) as f64;
+        Some((duration / characters).into())
+    }
+
+    /// Replay the keys pressed up to `elapsed` into the line
+    pub fn playback(&self, elapsed: Duration) -> Playback {
+        let text: Vec<char> = self.text.chars().collect();
+        let mut playback = Playback::default();
+        let mut missed = false;
+
+        for stroke in self
+            .strokes
+            .iter()
+            .take_while(|stroke| stroke.elapsed() <= elapsed)
+        {
+            let Some(&target) = text.get(playback.typed.len()) else {
+                break;
+            };
+            match stroke.key {
+                Key::Backspace => {
+                    playback.errors.pop();
+                }
+                // Tab at the start of a line of code skips over the
+                // indentation, as in training
+                Key::Char('\t') if playback.at_indentation(&text) => {
+                    while text.get(playback.typed.len()) == Some(&' ') {
+                        playback.typed.push(missed);
+                        missed = false;
+                    }
+                }
+                Key::Char(c) if c == target && playback.errors.is_empty() => {
+                    playback.typed.push(missed);
+                    missed = false;
+                }
+                Key::Char(c) => {
+                    missed = true;
+                    playback.misses += 1;
+                    if playback.errors.len() == MAX_ERRORS {
+                        playback.errors.pop();
+                    }
+                    playback.errors.push(c);
+                }
+            }
+        }
+
+        playback
+    }
+}
+
+impl Ghost {
+    /// Number of characters the ghost has typed, `elapsed` into the line
+    pub fn position(&self, best: Option<&Recording>, elapsed: Duration) -> Option<usize> {
+        let recording = match self {
+            Ghost::Off => None,
+            Ghost::Best => best,
+            Ghost::Line(recording) => Some(recording),
+        }?;
+
+        Some(recording.playback(elapsed).typed.len())
+    }
+
+    pub fn kind(&self) -> GhostKind {
+        match self {
+            Ghost::Off => GhostKind::Off,
+            Ghost::Best => GhostKind::Best,
+            Ghost::Line(_) => GhostKind::Line,
+        }
+    }
+}
+
+impl std::fmt::Display for GhostKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            GhostKind::Off => "Off",
+            GhostKind::Best => "Personal Best",
+            GhostKind::Line => "Chosen Line",
+        };
+
+        write!(f, "{}", s)
+    }
+}
+
+impl Stroke {
+    pub fn new(key: Key, elapsed: Duration) -> Self {
+        Self {
+            at: elapsed.whole_milliseconds().clamp(0, u32::MAX as i128) as u32,
+            key,
+        }
+    }
+
+    /// Time since the line started
+    pub fn elapsed(&self) -> Duration {
+        Duration::milliseconds(self.at as i64)
+    }
+}
+
+impl Playback {
+    /// Whether everything typed so far, and the next target, is leading
+    /// whitespace
+    fn at_indentation(&self, text: &[char]) -> bool {
+        self.errors.is_empty()
+            && text.get(self.typed.len()) == Some(&' ')
+            && text[..self.typed.len()].iter().all(|&c| c == ' ')
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// "cat" typed as c, x, backspace, a, t, 100 ms apart
+    fn recording() -> Recording {
+        let keys = [
+            Key::Char('c'),
+            Key::Char('x'),
+            Key::Backspace,
+            Key::Char('a'),
+            Key::Char('t'),
+        ];
+        Recording {
+            time: OffsetDateTime::unix_epoch(),
+            text: "cat".to_string(),
+            strokes: keys
+                .iter()
+                .enumerate()
+                .map(|(i, &key)| Stroke::new(key, Duration::milliseconds(i as i64 * 100)))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn playback_follows_the_keys() {
+        let recording = recording();
+
+        let playback = recording.playback(Duration::milliseconds(150));
+        assert_eq!(playback.typed, [false]);
+        assert_eq!(playback.errors, ['x']);
+        assert_eq!(playback.misses, 1);
+
+        let playback = recording.playback(Duration::milliseconds(400));
+        assert_eq!(playback.typed, [false, true, false]);
+        assert!(playback.errors.is_empty());
+        assert_eq!(playback.misses, 1);
+    }
+
+    #[test]
+    fn speed_is_timed_from_the_first_key() {
+        let recording = recording();
+
+        // Three characters in 400 ms is 133.3 ms a character, or 90 wpm
+        assert_eq!(recording.duration(), Duration::milliseconds(400));
+        assert_eq!(f64::from(recording.wpm().unwrap()).round(), 90.0);
+    }
+
+    #[test]
+    fn ghost_moves_with_the_recording() {
+        let recording = recording();
+        let best = Ghost::Best;
+
+        assert_eq!(
+            Ghost::Off.position(Some(&recording), Duration::seconds(1)),
+            None
+        );
+        assert_eq!(best.position(None, Duration::seconds(1)), None);
+        assert_eq!(best.position(Some(&recording), Duration::zero()), Some(1));
+        assert_eq!(
+            best.position(Some(&recording), Duration::milliseconds(300)),
+            Some(2)
+        );
+        assert_eq!(
+            best.position(Some(&recording), Duration::seconds(1)),
+            Some(3)
+        );
+    }
+}
@@ -0,0 +1,428 @@
+use super::clock::Clock;
+use super::profile::{Active, Profile};
+use super::training::Session;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::{HashMap, VecDeque};
+use time::{Duration, OffsetDateTime};
+
+/// Key pressed in place of the target when a simulated typist makes a mistake
+const WRONG_KEY: char = '\u{fffd}';
+/// Lowest accuracy a modelled typist can have, so every target is reached
+const MIN_ACCURACY: f64 = 0.05;
+const MAX_JITTER: f64 = 0.9;
+
+/// Decides which key a simulated typist presses for a target, and how long
+/// it takes them
+pub trait Typist {
+    fn press(&mut self, target: char) -> Keystroke;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Keystroke {
+    /// The key pressed, which is a mistake when it is not the target
+    pub key: char,
+    /// Time since the previous keystroke
+    pub delay: Duration,
+}
+
+/// Presses the keys of a script in order, whatever the target. Once the
+/// script runs out, every target is typed correctly at a fixed delay.
+#[derive(Debug, Clone)]
+pub struct Scripted {
+    keystrokes: VecDeque<Keystroke>,
+    delay: Duration,
+}
+
+/// Typist with a steady speed and accuracy, varying at random around them.
+/// Letters can be given their own speed and accuracy to model weak spots.
+#[derive(Debug, Clone)]
+pub struct Modelled {
+    wpm: f64,
+    accuracy: f64,
+    /// Relative variation in the time between keystrokes
+    jitter: f64,
+    letters: HashMap<char, (f64, f64)>,
+    rng: StdRng,
+}
+
+/// Feeds a typist's keystrokes through a training session with a manual
+/// clock, adding each completed line to the profile as the app would
+pub struct Simulation<T> {
+    profile: Active,
+    typist: T,
+    lines: usize,
+}
+
+/// A letter added to the training set during a simulation
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Unlock {
+    pub letter: char,
+    /// Number of lines typed when the letter was unlocked
+    pub line: usize,
+    /// Simulated time spent typing until then
+    pub elapsed: Duration,
+}
+
+impl Keystroke {
+    pub fn new(key: char, delay: Duration) -> Self {
+        Self { key, delay }
+    }
+}
+
+impl Scripted {
+    pub fn new(keystrokes: impl IntoIterator<Item = Keystroke>, delay: Duration) -> Self {
+        Self {
+            keystrokes: keystrokes.into_iter().collect(),
+            delay,
+        }
+    }
+}
+
+impl Typist for Scripted {
+    fn press(&mut self, target: char) -> Keystroke {
+        self.keystrokes
+            .pop_front()
+            .unwrap_or(Keystroke::new(target, self.delay))
+    }
+}
+
+/// Any function from target to keystroke is a typist, for one-off scripts
+impl<F: FnMut(char) -> Keystroke> Typist for F {
+    fn press(&mut self, target: char) -> Keystroke {
+        self(target)
+    }
+}
+
+impl Modelled {
+    /// Typist averaging `wpm` who presses the right key with probability
+    /// `accuracy`, drawing every choice from `seed`
+    pub fn new(wpm: f64, accuracy: f64, seed: u64) -> Self {
+        Self {
+            wpm,
+            accuracy,
+            jitter: 0.25,
+            letters: HashMap::new(),
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    /// Vary the time between keystrokes by up to this proportion either way
+    pub fn jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter.clamp(0.0, MAX_JITTER);
+        self
+    }
+
+    /// Type one letter at its own speed and accuracy
+    pub fn letter(mut self, letter: char, wpm: f64, accuracy: f64) -> Self {
+        self.letters.insert(letter, (wpm, accuracy));
+        self
+    }
+}
+
+impl Typist for Modelled {
+    fn press(&mut self, target: char) -> Keystroke {
+        let (wpm, accuracy) = self
+            .letters
+            .get(&target)
+            .cloned()
+            .unwrap_or((self.wpm, self.accuracy));
+
+        // Five characters to a word
+        let seconds = 12.0 / wpm.max(1.0);
+        let variation = 1.0 + self.jitter * self.rng.gen_range(-1.0..=1.0);
+        let key = if self.rng.gen_bool(accuracy.clamp(MIN_ACCURACY, 1.0)) {
+            target
+        } else {
+            WRONG_KEY
+        };
+
+        Keystroke::new(key, Duration::seconds_f64(seconds * variation))
+    }
+}
+
+impl<T: Typist> Simulation<T> {
+    /// Train the profile with lines generated from `seed`. The clock starts
+    /// at the Unix epoch, so the same inputs always give the same results.
+    pub fn new(profile: Profile, typist: T, seed: u64) -> Self {
+        let mut profile = Active::from(profile);
+        profile.session = Session::new(
+            &profile.session.words_setting(),
+            &profile.state,
+            &profile.layout,
+            profile.language,
+            seed,
+        )
+        .with_clock(Clock::manual(OffsetDateTime::unix_epoch()));
+
+        Self {
+            profile,
+            typist,
+            lines: 0,
+        }
+    }
+
+    /// Type a whole line, correcting each mistake with backspace straight
+    /// away. Returns the letter unlocked by the line, if any.
+    pub fn type_line(&mut self) -> Option<Unlock> {
+        loop {
+            let session = &mut self.profile.session;
+            let target = session.active_hit.target();
+            let keystroke = self.typist.press(target);
+            session.clock_mut().advance(keystroke.delay);
+
+            if let Some(line) = session.apply_char(keystroke.key) {
+                self.lines += 1;
+                let before = self.profile.state.char_set();
+                if let Some(words) = self.profile.add_line(line) {
+                    self.profile.session.update_words(words);
+                }
+                let profile = &mut self.profile;
+                profile
+                    .session
+                    .fill_next_lines(&profile.state, &profile.targets());
+
+                return profile
+                    .state
+                    .char_set()
+                    .difference(&before)
+                    .next()
+                    .map(|&letter| Unlock {
+                        letter,
+                        line: self.lines,
+                        elapsed: self.elapsed(),
+                    });
+            }
+            if keystroke.key != target {
+                session.backspace();
+            }
+        }
+    }
+
+    /// Type `lines` lines, returning the letters unlocked along the way
+    pub fn type_lines(&mut self, lines: usize) -> Vec<Unlock> {
+        (0..lines).filter_map(|_| self.type_line()).collect()
+    }
+
+    /// The profile being trained
+    pub fn profile(&self) -> &Active {
+        &self.profile
+    }
+
+    /// The profile being trained, to change its settings part way through
+    pub fn profile_mut(&mut self) -> &mut Active {
+        &mut self.profile
+    }
+
+    /// Simulated time spent typing so far
+    pub fn elapsed(&self) -> Duration {
+        self.profile.session.clock().elapsed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::keyboard::Layout;
+    use crate::data::language::Language;
+    use crate::data::profile::Name;
+    use crate::data::training::{Difficulty, Targets, SEEDED_LINES};
+
+    const SEED: u64 = 7;
+    const LINES: usize = 20;
+
+    fn profile(difficulty: Difficulty) -> Profile {
+        Profile::new(
+            Name::parse("Simulation").unwrap(),
+            Layout::default(),
+            Language::default(),
+            difficulty,
+        )
+    }
+
+    /// Letters unlocked by a steady typist over `LINES` lines
+    fn unlocks(difficulty: Difficulty, wpm: f64, accuracy: f64) -> Vec<Unlock> {
+        let typist = Modelled::new(wpm, accuracy, SEED).jitter(0.0);
+        Simulation::new(profile(difficulty), typist, SEED).type_lines(LINES)
+    }
+
+    #[test]
+    fn each_difficulty_unlocks_above_its_speed() {
+        for &difficulty in Difficulty::ALL {
+            let Some(targets) = difficulty.preset() else {
+                continue;
+            };
+            let target = f64::from(targets.wpm);
+
+            let fast = unlocks(difficulty, 1.25 * target, 1.0);
+            assert!(!fast.is_empty(), "{} at {} wpm", difficulty, 1.25 * target);
+            assert_eq!(fast[0].letter, 'k');
+
+            let slow = unlocks(difficulty, 0.8 * target, 1.0);
+            assert!(slow.is_empty(), "{} at {} wpm", difficulty, 0.8 * target);
+        }
+    }
+
+    #[test]
+    fn custom_targets_set_the_pace() {
+        let unlocks = |targets: Targets, wpm: f64, accuracy: f64| {
+            let typist = Modelled::new(wpm, accuracy, SEED).jitter(0.0);
+            let mut simulation = Simulation::new(profile(Difficulty::Custom), typist, SEED);
+            simulation.profile_mut().custom = targets;
+            simulation.type_lines(LINES)
+        };
+        let fast = Targets::new(60.0, 0.75, false);
+        let accurate = Targets::new(20.0, 0.8, false);
+
+        assert!(!unlocks(fast, 75.0, 1.0).is_empty());
+        assert!(unlocks(fast, 50.0, 1.0).is_empty());
+        assert!(!unlocks(accurate, 40.0, 1.0).is_empty());
+        assert!(unlocks(accurate, 40.0, 0.7).is_empty());
+    }
+
+    #[test]
+    fn letters_unlock_in_layout_order() {
+        let letters: Vec<char> = unlocks(Difficulty::Easy, 40.0, 1.0)
+            .iter()
+            .map(|unlock| unlock.letter)
+            .collect();
+
+        assert!(letters.len() >= 3, "{:?}", letters);
+        assert_eq!(letters[..3], ['k', 'i', 'l']);
+    }
+
+    #[test]
+    fn reordered_letters_unlock_first() {
+        let typist = Modelled::new(40.0, 1.0, SEED).jitter(0.0);
+        let mut simulation = Simulation::new(profile(Difficulty::Easy), typist, SEED);
+        simulation.profile_mut().reorder(&['n', 'r']);
+        let letters: Vec<char> = simulation
+            .type_lines(LINES)
+            .iter()
+            .map(|unlock| unlock.letter)
+            .collect();
+
+        assert!(letters.len() >= 3, "{:?}", letters);
+        assert_eq!(letters[..3], ['n', 'r', 'k']);
+    }
+
+    #[test]
+    fn locked_letters_unlock_again() {
+        let typist = Modelled::new(40.0, 1.0, SEED).jitter(0.0);
+        let mut simulation = Simulation::new(profile(Difficulty::Easy), typist, SEED);
+        simulation.profile_mut().unlock('r');
+        simulation.profile_mut().lock('e').unwrap();
+        assert!(!simulation.profile().state.char_set().contains(&'e'));
+
+        let unlocks = simulation.type_lines(LINES);
+        assert!(!unlocks.is_empty());
+        assert_eq!(unlocks[0].letter, 'e');
+    }
+
+    #[test]
+    fn unlocked_letters_must_be_learnt() {
+        let first_unlock = |unlocked: &[char]| {
+            let typist = Modelled::new(40.0, 1.0, SEED).jitter(0.0);
+            let mut simulation = Simulation::new(profile(Difficulty::Easy), typist, SEED);
+            for &letter in unlocked {
+                simulation.profile_mut().unlock(letter);
+            }
+            simulation
+                .type_lines(LINES)
+                .first()
+                .map(|unlock| unlock.line)
+        };
+
+        let plain = first_unlock(&[]).expect("unlocks without extra letters");
+        let extra = first_unlock(&['r', 't']).expect("unlocks once they are learnt");
+        assert!(extra > plain, "{} then {}", plain, extra);
+    }
+
+    #[test]
+    fn mistakes_hold_back_unlocks() {
+        assert!(unlocks(Difficulty::Easy, 60.0, 0.5).is_empty());
+    }
+
+    #[test]
+    fn same_seed_gives_the_same_schedule() {
+        let run = || {
+            let typist = Modelled::new(50.0, 0.97, SEED);
+            let mut simulation = Simulation::new(profile(Difficulty::Normal), typist, SEED);
+            (simulation.type_lines(LINES), simulation.elapsed())
+        };
+
+        assert_eq!(run(), run());
+    }
+
+    #[test]
+    fn replaying_a_seed_gives_the_same_lines() {
+        // Too slow to unlock anything, so the letters stay the same
+        let typist = Modelled::new(10.0, 0.9, SEED);
+        let mut simulation = Simulation::new(profile(Difficulty::Normal), typist, SEED);
+        let type_seeded = |simulation: &mut Simulation<Modelled>| {
+            (0..SEEDED_LINES)
+                .map(|_| {
+                    simulation.type_line();
+                    let recordings = simulation.profile().state.recordings();
+                    recordings[recordings.len() - 1].text.clone()
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let first = type_seeded(&mut simulation);
+        simulation.profile_mut().replay(SEED);
+        let replayed = type_seeded(&mut simulation);
+
+        assert_eq!(first, replayed);
+    }
+
+    #[test]
+    fn scripted_typist_unlocks_at_its_own_pace() {
+        let unlocks = |ms| {
+            let typist = Scripted::new([], Duration::milliseconds(ms));
+            Simulation::new(profile(Difficulty::Strict), typist, SEED).type_lines(LINES)
+        };
+
+        // 120 and 30 wpm, either side of the strict target
+        assert!(!unlocks(100).is_empty());
+        assert!(unlocks(400).is_empty());
+    }
+
+    #[test]
+    fn mistakes_delay_the_first_unlock() {
+        let first_unlock = |mistakes: usize| {
+            // Miss every other target until the mistakes run out
+            let mut presses = 0;
+            let typist = move |target| {
+                presses += 1;
+                let key = if presses <= 2 * mistakes && presses % 2 == 1 {
+                    WRONG_KEY
+                } else {
+                    target
+                };
+                Keystroke::new(key, Duration::milliseconds(100))
+            };
+            Simulation::new(profile(Difficulty::Normal), typist, SEED)
+                .type_lines(LINES)
+                .first()
+                .map(|unlock| unlock.line)
+        };
+
+        let clean = first_unlock(0).expect("unlocks without mistakes");
+        let sloppy = first_unlock(600).expect("unlocks once mistakes stop");
+        assert!(sloppy > clean, "{} then {}", clean, sloppy);
+    }
+
+    #[test]
+    fn manual_clock_counts_typing_time() {
+        let typist = |target| Keystroke::new(target, Duration::milliseconds(250));
+        let mut simulation = Simulation::new(profile(Difficulty::Normal), typist, SEED);
+        simulation.type_line();
+
+        let presses = simulation.profile().state.recordings()[0].strokes.len();
+        assert_eq!(
+            simulation.elapsed(),
+            Duration::milliseconds(250) * presses as u32
+        );
+    }
+}
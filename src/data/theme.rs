@@ -1,6 +1,15 @@
-use iced::Color;
 use palette::{Mix, Srgb};
 
+/// Color with red, green, blue and alpha from 0.0 to 1.0, for any front end
+/// to draw with
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Color {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
+}
+
 #[derive(Debug, Clone)]
 pub struct Theme {
     pub name: &'static str,
@@ -116,7 +125,7 @@ impl Theme {
     /// Between 0.0 and 0.5, return a blend from error -> text
     /// Between 0.5 and 1.0, return a blend from text -> target
     pub fn metric(&self, value: f32) -> Color {
-        let value = value.min(1.0).max(0.0);
+        let value = value.clamp(0.0, 1.0);
         let text = Srgb::from(self.text).into_linear();
         if value < 0.5 {
             let pct = value / 0.5;
@@ -130,8 +139,32 @@ impl Theme {
     }
 }
 
-pub fn alpha(color: Color, alpha: f32) -> Color {
-    Color { a: alpha, ..color }
+impl Color {
+    pub fn from_rgba8(r: u8, g: u8, b: u8, a: f32) -> Self {
+        Self {
+            r: f32::from(r) / 255.0,
+            g: f32::from(g) / 255.0,
+            b: f32::from(b) / 255.0,
+            a,
+        }
+    }
+}
+
+impl From<Color> for Srgb {
+    fn from(color: Color) -> Self {
+        Srgb::new(color.r, color.g, color.b)
+    }
+}
+
+impl From<Srgb> for Color {
+    fn from(color: Srgb) -> Self {
+        Color {
+            r: color.red,
+            g: color.green,
+            b: color.blue,
+            a: 1.0,
+        }
+    }
 }
 
 impl Default for Theme {
@@ -1,18 +1,55 @@
-use super::keyboard::Layout;
+use super::clock::Clock;
+use super::keyboard::{Finger, Fingers, Hand, Layout, FINGERS, INITIAL_CHARS};
+use super::language::Language;
+use super::recording::{Key, Recording, Stroke};
 use super::words::{self, Words};
 use super::CharSet;
 use itertools::Itertools;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
 use serde::{Deserialize, Serialize};
-use statrs::statistics::{self, Distribution, Statistics};
 use std::collections::{HashMap, HashSet, VecDeque};
-use time::{Duration, Instant, OffsetDateTime};
+use time::{Duration, OffsetDateTime};
 
 pub const CHARS_PER_LINE: usize = 52;
 pub const NEXT_LINES: usize = 1;
+/// Lines at the start of a session generated from its seed and letters
+/// alone, so that a seed always gives them again
+pub const SEEDED_LINES: usize = 10;
 pub const MAX_ERRORS: usize = 5;
 pub const NUM_RECENT_TIMINGS: usize = 16;
-pub const CLEAN_ALPHA_COEFF: f32 = 1.0 / (1.0 + 10.0);
+/// Age at which a timing counts for half as much as one typed just now
+pub const TIMING_HALF_LIFE_DAYS: f64 = 7.0;
+/// Days after which a letter that has not been typed cleanly is due for
+/// review
+pub const REVIEW_AFTER_DAYS: i64 = 3;
+/// Misses a clean proportion averages over at the accuracy target, so one
+/// miss moves it about as far whatever the target
+pub const CLEAN_WINDOW_MISSES: f32 = 2.5;
+/// Most hits a clean proportion averages over, so that a new letter can
+/// still reach a strict target in a few sessions
+pub const MAX_CLEAN_WINDOW: f32 = 50.0;
 pub const MIN_CLEAN_PCT: f32 = 0.75;
+/// Range allowed for a custom speed target
+pub const TARGET_WPM_RANGE: std::ops::RangeInclusive<f64> = 5.0..=200.0;
+/// Range allowed for a custom accuracy target. Smoothing means a clean
+/// proportion only approaches 1, so it cannot be the target.
+pub const TARGET_ACCURACY_RANGE: std::ops::RangeInclusive<f32> = 0.5..=0.99;
+/// Speed added to the target each time it is raised automatically
+pub const RAISE_WPM: f64 = 5.0;
+/// Number of weak bigrams used to bias line generation
+pub const NUM_WEAK_BIGRAMS: usize = 3;
+/// Completed lines between progress points in the event log
+pub const PROGRESS_INTERVAL_LINES: u64 = 25;
+/// Progress points between statistical rollups in the event log
+pub const CHECKPOINT_INTERVAL: u64 = 4;
+/// Number of recent lines kept with every keystroke, for playing back
+pub const MAX_RECORDINGS: usize = 50;
+/// Shortest line that can become the personal best. A line of a key or two,
+/// such as a closing bracket in code, would be impossible to beat.
+const MIN_BEST_CHARS: usize = 10;
+/// Samples required before a bigram's statistics are considered meaningful
+const MIN_BIGRAM_SAMPLES: usize = 4;
 const CHARACTERS_PER_WORD: f64 = 5.0;
 
 #[derive(Debug, Default, Clone, Deserialize, Serialize)]
@@ -23,10 +60,54 @@ pub struct State {
     pub timings: HashMap<char, Stats>,
     /// Smoothed clean proportion
     clean: HashMap<char, f32>,
+    /// Stats for each transition between two characters
+    #[serde(default)]
+    pub bigram_timings: HashMap<Bigram, Stats>,
+    /// Smoothed clean proportion for each transition
+    #[serde(default)]
+    bigram_clean: HashMap<Bigram, f32>,
     /// Event log
     events: Vec<Event>,
+    /// Summary of every completed line
+    #[serde(default)]
+    history: Vec<LineRecord>,
+    /// Running totals across all training
+    #[serde(default)]
+    totals: Totals,
+    /// Keystrokes of the most recent lines, oldest first
+    #[serde(default)]
+    recordings: Vec<Recording>,
+    /// Keystrokes of the fastest line typed
+    #[serde(default)]
+    best: Option<Recording>,
+    /// Letters to unlock ahead of the rest of the layout's order, as chosen
+    /// by hand
+    #[serde(default)]
+    order: Vec<char>,
 }
 
+/// Running totals across all training
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct Totals {
+    pub time_training: Duration,
+    pub lines_typed: u64,
+    pub characters_typed: u64,
+    pub top_speed_wpm: WordsPerMinute,
+}
+
+/// Snapshot of one character's statistics at a checkpoint
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Rollup {
+    pub wpm_mean: WordsPerMinute,
+    pub wpm_harmonic_mean: WordsPerMinute,
+    pub clean: f32,
+}
+
+/// Two characters typed one after the other
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(into = "String", try_from = "String")]
+pub struct Bigram(pub char, pub char);
+
 #[derive(Debug, Clone, Copy, Default, PartialEq, PartialOrd, Deserialize, Serialize)]
 pub struct WordsPerMinute(f64);
 
@@ -37,11 +118,30 @@ pub enum Difficulty {
     #[default]
     Normal,
     Strict,
+    /// The profile's own targets
+    Custom,
+}
+
+/// Speed and accuracy every letter in training must reach before the next
+/// letter is unlocked
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub struct Targets {
+    pub wpm: WordsPerMinute,
+    /// Smoothed proportion of hits typed without a miss, from 0 to 1
+    pub accuracy: f32,
+    /// Raise the speed target by `RAISE_WPM` whenever every letter is
+    /// unlocked and has reached it
+    #[serde(default)]
+    pub auto_raise: bool,
 }
 
 #[derive(Debug, Default, Clone, Deserialize, Serialize)]
 pub struct Stats {
     raw: Vec<WordsPerMinute>,
+    /// When each sample was typed, lined up with the end of `raw`. Samples
+    /// saved before times were kept have none.
+    #[serde(default)]
+    times: Vec<OffsetDateTime>,
     pub wpm_mean: WordsPerMinute,
     pub wpm_harmonic_mean: WordsPerMinute,
 }
@@ -50,27 +150,61 @@ pub struct Stats {
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub enum Event {
     /// New letter added to our training set
-    Unlock { letter: char, time: OffsetDateTime },
-    // /// Computed progress point
-    // Progress {
-    //     time: OffsetDateTime,
-    //     total_time_training: Duration,
-    //     total_lines_typed: u64,
-    //     total_characters_typed: u64,
-    //     top_speed_wpm: f64,
-    //     average_speed_wpm: f64,
-    //     num_characters: u8,
-    // },
-    // /// Statistical rollup
-    // Checkpoint {},
+    Unlock {
+        letter: char,
+        time: OffsetDateTime,
+        /// Added by hand rather than by reaching the targets
+        #[serde(default)]
+        manual: bool,
+    },
+    /// Letter taken out of our training set by hand
+    Lock { letter: char, time: OffsetDateTime },
+    /// Letters still to unlock put in a new order by hand
+    Reorder {
+        order: Vec<char>,
+        time: OffsetDateTime,
+    },
+    /// Computed progress point, recorded every `PROGRESS_INTERVAL_LINES`
+    Progress {
+        time: OffsetDateTime,
+        total_time_training: Duration,
+        total_lines_typed: u64,
+        total_characters_typed: u64,
+        top_speed_wpm: f64,
+        /// Average speed over the lines since the previous progress point
+        average_speed_wpm: f64,
+        num_characters: u8,
+    },
+    /// Statistical rollup of every character, recorded every
+    /// `CHECKPOINT_INTERVAL` progress points
+    Checkpoint {
+        time: OffsetDateTime,
+        letters: HashMap<char, Rollup>,
+    },
 }
 
 #[derive(Debug, Clone)]
 pub struct Session {
     /// Words to choose from
     words: Words,
-    /// The instant when the last hit was completed
-    baseline: Instant,
+    /// Seed that the session's lines are generated from
+    seed: u64,
+    rng: StdRng,
+    /// Lines generated from the seed when the session started, still to be
+    /// typed. Lines after these adapt to the training state.
+    seeded: VecDeque<String>,
+    /// Finger pressing each key of the profile's layout
+    fingers: Fingers,
+    /// Keystrokes are timed by this clock
+    clock: Clock,
+    /// Time on the clock when the last hit was completed
+    baseline: Duration,
+    /// Time on the clock when the first key of the current line was pressed
+    line_start: Duration,
+    /// The text of the current line
+    line_text: String,
+    /// Keys pressed so far on the current line
+    strokes: Vec<Stroke>,
     /// The current hit we're working on
     pub active_hit: Hit,
     /// List of completed hits
@@ -87,17 +221,69 @@ pub struct Session {
 #[derive(Debug, Clone)]
 pub struct Line {
     hits: Vec<Hit>,
-    #[allow(dead_code)]
+    /// The text of the line as shown
+    text: String,
+    /// Every key pressed while typing the line
+    strokes: Vec<Stroke>,
     time: OffsetDateTime,
 }
 
+/// Speed and accuracy of the letters in training pressed by one finger
+#[derive(Debug, Clone)]
+pub struct FingerStats {
+    pub finger: Finger,
+    /// Mean speed of the letters
+    pub wpm: WordsPerMinute,
+    /// Mean smoothed clean proportion of the letters
+    pub clean: f32,
+    pub letters: Vec<char>,
+}
+
+/// Speed and accuracy of the letters in training pressed by one hand
+#[derive(Debug, Clone)]
+pub struct HandStats {
+    pub hand: Hand,
+    /// Mean speed of the letters
+    pub wpm: WordsPerMinute,
+    /// Mean smoothed clean proportion of the letters
+    pub clean: f32,
+    /// Proportion of the characters in recent lines typed by this hand
+    pub share: f32,
+}
+
+/// Speed of transitions typed with one finger on two different keys,
+/// against every other transition
+#[derive(Debug, Clone, Copy)]
+pub struct SameFinger {
+    pub same: WordsPerMinute,
+    pub other: WordsPerMinute,
+}
+
+/// Compact summary of a completed line, kept for long-term analysis
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct LineRecord {
+    /// When the line was completed
+    pub time: OffsetDateTime,
+    /// Time spent typing the line
+    pub duration: Duration,
+    /// Number of characters typed
+    pub characters: u16,
+    /// Number of incorrect keys pressed
+    pub errors: u16,
+    /// Typing speed across the line
+    pub wpm: WordsPerMinute,
+    /// Proportion of characters typed without a miss
+    pub accuracy: f32,
+    /// Number of characters in the training set
+    pub unlocked: u16,
+}
+
 /// A successful keystroke
 #[derive(Debug, Clone)]
 pub struct Hit {
     /// The character to type
     target: char,
     /// The previous character typed
-    #[allow(dead_code)]
     prev: char,
     /// Incorrect keys hit instead of the target
     misses: CharSet,
@@ -109,7 +295,11 @@ pub struct Hit {
 impl State {
     pub fn new(chars: Vec<char>) -> Self {
         let char_set = chars.iter().cloned().collect();
-        let events = chars.iter().map(|&letter| Event::unlock(letter)).collect();
+        let now = OffsetDateTime::now_utc();
+        let events = chars
+            .iter()
+            .map(|&letter| Event::unlock(letter, now, false))
+            .collect();
         let clean = chars.iter().map(|&letter| (letter, 0.0)).collect();
         let timings = chars
             .iter()
@@ -120,50 +310,259 @@ impl State {
             char_set,
             timings,
             clean,
+            bigram_timings: HashMap::new(),
+            bigram_clean: HashMap::new(),
             events,
+            history: Vec::new(),
+            totals: Totals::default(),
+            recordings: Vec::new(),
+            best: None,
+            order: Vec::new(),
         }
     }
 
-    fn char_set(&self) -> CharSet {
+    pub fn char_set(&self) -> CharSet {
         self.char_set.clone()
     }
 
-    /// Get up to the `n` letters that need improvement (accuracy then speed)
-    fn needs_improvement(&self, n: usize) -> CharSet {
-        let unclean: CharSet = self
+    /// Get up to the `n` letters that need improvement (overdue for review
+    /// at `now`, then below the accuracy target, then speed)
+    fn needs_improvement(&self, n: usize, now: OffsetDateTime, targets: &Targets) -> CharSet {
+        let overdue = self.due_for_review(now).into_iter().map(|(ch, _)| ch);
+        let unclean = self
             .clean
             .iter()
-            .filter(|(_, &pct)| pct < MIN_CLEAN_PCT)
-            .sorted_by_key(|(_, v)| (*v * 1000.0).round() as u16)
-            .map(|(ch, _)| ch)
+            .filter(|(ch, &pct)| pct < targets.accuracy && self.in_training(ch))
+            .sorted_by_key(|(ch, v)| ((*v * 1000.0).round() as u16, **ch))
+            .map(|(ch, _)| *ch);
+        let slow = self
+            .timings
+            .iter()
+            .filter(|(ch, _)| self.in_training(ch))
+            .sorted_by_key(|(ch, stats)| {
+                ((stats.wpm_harmonic_mean.0 * 1000.0).round() as u32, **ch)
+            })
+            .map(|(ch, _)| *ch);
+
+        overdue
+            .chain(unclean)
+            .chain(slow)
+            .unique()
+            .take(n)
+            .collect()
+    }
+
+    /// Letters in training not typed cleanly for `REVIEW_AFTER_DAYS` before
+    /// `now`, with the time since they last were, longest first
+    pub fn due_for_review(&self, now: OffsetDateTime) -> Vec<(char, Duration)> {
+        self.timings
+            .iter()
+            .filter(|(ch, _)| **ch != ' ' && self.char_set.contains(ch))
+            .filter_map(|(&ch, stats)| Some((ch, now - stats.last_practised()?)))
+            .filter(|(_, since)| *since >= Duration::days(REVIEW_AFTER_DAYS))
+            .sorted_by_key(|&(ch, since)| (std::cmp::Reverse(since), ch))
+            .collect()
+    }
+
+    /// Letters to focus on in new lines: the one most in need of improvement
+    /// at `now`, plus the weakest letter of the weakest finger
+    pub fn focus(&self, fingers: &Fingers, now: OffsetDateTime, targets: &Targets) -> CharSet {
+        let mut focus = self.needs_improvement(1, now, targets);
+        let weakness = |clean, wpm| weakness(clean, wpm, targets);
+        let weakest = self
+            .finger_stats(fingers)
+            .into_iter()
+            .min_by(|a, b| weakness(a.clean, a.wpm).total_cmp(&weakness(b.clean, b.wpm)));
+        let letter = weakest.and_then(|finger| {
+            finger.letters.into_iter().min_by(|a, b| {
+                let [a, b] = [a, b].map(|c| {
+                    let clean = self.clean.get(c).cloned().unwrap_or_default();
+                    let wpm = self
+                        .timings
+                        .get(c)
+                        .map(|stats| stats.wpm_harmonic_mean)
+                        .unwrap_or_default();
+                    weakness(clean, wpm)
+                });
+                a.total_cmp(&b)
+            })
+        });
+        focus.extend(letter);
+
+        focus
+    }
+
+    /// Speed and accuracy for each finger pressing letters in training
+    pub fn finger_stats(&self, fingers: &Fingers) -> Vec<FingerStats> {
+        FINGERS
+            .iter()
+            .filter_map(|&finger| {
+                let letters: Vec<char> = self
+                    .char_set
+                    .iter()
+                    .filter(|c| fingers.get(c) == Some(&finger))
+                    .cloned()
+                    .sorted()
+                    .collect();
+                (!letters.is_empty()).then(|| FingerStats {
+                    finger,
+                    wpm: self.mean_wpm(&letters),
+                    clean: self.mean_clean(&letters),
+                    letters,
+                })
+            })
+            .collect()
+    }
+
+    /// Speed, accuracy and share of the typing for each hand
+    pub fn hand_stats(&self, fingers: &Fingers) -> Vec<HandStats> {
+        let typed: Vec<Hand> = self
+            .recordings
+            .iter()
+            .flat_map(|recording| recording.text.chars())
+            .filter_map(|c| fingers.get(&c).map(Finger::hand))
+            .collect();
+
+        [Hand::Left, Hand::Right]
+            .into_iter()
+            .map(|hand| {
+                let letters: Vec<char> = self
+                    .char_set
+                    .iter()
+                    .filter(|c| fingers.get(c).map(Finger::hand) == Some(hand))
+                    .cloned()
+                    .collect();
+                let count = typed.iter().filter(|&&h| h == hand).count();
+                HandStats {
+                    hand,
+                    wpm: self.mean_wpm(&letters),
+                    clean: self.mean_clean(&letters),
+                    share: count as f32 / typed.len().max(1) as f32,
+                }
+            })
+            .collect()
+    }
+
+    /// How transitions typed with a single finger compare with the rest, once
+    /// both have been measured
+    pub fn same_finger(&self, fingers: &Fingers) -> Option<SameFinger> {
+        let (same, other): (Vec<f64>, Vec<f64>) = self
+            .bigram_timings
+            .iter()
+            .filter(|(bigram, _)| self.has_samples(bigram))
+            .map(|(bigram, stats)| {
+                let same = bigram.0.to_lowercase().ne(bigram.1.to_lowercase())
+                    && fingers.get(&bigram.0).is_some()
+                    && fingers.get(&bigram.0) == fingers.get(&bigram.1);
+                (same, f64::from(stats.wpm_harmonic_mean))
+            })
+            .partition_map(|(same, wpm)| {
+                if same {
+                    itertools::Either::Left(wpm)
+                } else {
+                    itertools::Either::Right(wpm)
+                }
+            });
+        if same.is_empty() || other.is_empty() {
+            return None;
+        }
+
+        let mean = |values: &[f64]| {
+            WordsPerMinute(round(values.iter().sum::<f64>() / values.len() as f64, 2))
+        };
+        Some(SameFinger {
+            same: mean(&same),
+            other: mean(&other),
+        })
+    }
+
+    /// Mean speed of the letters measured so far
+    fn mean_wpm(&self, letters: &[char]) -> WordsPerMinute {
+        let speeds: Vec<f64> = letters
+            .iter()
+            .filter_map(|c| self.timings.get(c))
+            .map(|stats| f64::from(stats.wpm_harmonic_mean))
+            .filter(|&wpm| wpm > 0.0)
+            .collect();
+        if speeds.is_empty() {
+            return WordsPerMinute::default();
+        }
+        WordsPerMinute(round(speeds.iter().sum::<f64>() / speeds.len() as f64, 2))
+    }
+
+    /// Mean smoothed clean proportion of the letters
+    fn mean_clean(&self, letters: &[char]) -> f32 {
+        let clean: Vec<f32> = letters
+            .iter()
+            .filter_map(|c| self.clean.get(c))
             .cloned()
+            .collect();
+        clean.iter().sum::<f32>() / clean.len().max(1) as f32
+    }
+
+    /// Whether a character counts towards unlocking the next one: those in
+    /// our set and the space between words. Symbols typed in source code are
+    /// tracked too, but never hold back progress.
+    fn in_training(&self, ch: &char) -> bool {
+        *ch == ' ' || self.char_set.contains(ch)
+    }
+
+    /// Get up to `n` transitions that need improvement (below the accuracy
+    /// target, then speed)
+    fn weak_bigrams(&self, n: usize, targets: &Targets) -> Vec<Bigram> {
+        let unclean: Vec<Bigram> = self
+            .bigram_clean
+            .iter()
+            .filter(|(bigram, &pct)| pct < targets.accuracy && self.has_samples(bigram))
+            .sorted_by_key(|(bigram, v)| ((*v * 1000.0).round() as u16, bigram.0, bigram.1))
+            .map(|(bigram, _)| *bigram)
             .take(n)
             .collect();
+        let remaining = n.saturating_sub(unclean.len());
 
-        if unclean.len() < n {
-            unclean
-                .iter()
-                .chain(
-                    self.timings
-                        .iter()
-                        .sorted_by_key(
-                            |(
-                                _,
-                                Stats {
-                                    wpm_harmonic_mean, ..
-                                },
-                            )| {
-                                (wpm_harmonic_mean.0 * 1000.0).round() as u32
-                            },
-                        )
-                        .map(|(ch, _)| ch)
-                        .take(n.saturating_sub(unclean.len())),
+        unclean
+            .iter()
+            .cloned()
+            .chain(
+                self.slowest_bigrams(n)
+                    .into_iter()
+                    .map(|(bigram, _)| bigram)
+                    .filter(|bigram| !unclean.contains(bigram))
+                    .take(remaining),
+            )
+            .collect()
+    }
+
+    /// Get up to `n` of the slowest transitions between characters in our set
+    pub fn slowest_bigrams(&self, n: usize) -> Vec<(Bigram, &Stats)> {
+        self.bigram_timings
+            .iter()
+            .filter(|(bigram, _)| self.has_samples(bigram))
+            .sorted_by_key(|(bigram, stats)| {
+                (
+                    (stats.wpm_harmonic_mean.0 * 1000.0).round() as u32,
+                    bigram.0,
+                    bigram.1,
                 )
-                .cloned()
-                .collect()
-        } else {
-            unclean
-        }
+            })
+            .map(|(bigram, stats)| (*bigram, stats))
+            .take(n)
+            .collect()
+    }
+
+    /// Smoothed clean proportion for a transition
+    pub fn bigram_clean(&self, bigram: &Bigram) -> Option<f32> {
+        self.bigram_clean.get(bigram).cloned()
+    }
+
+    /// Whether the transition is in our set and has been typed often enough
+    fn has_samples(&self, bigram: &Bigram) -> bool {
+        self.char_set.contains(&bigram.0)
+            && self.char_set.contains(&bigram.1)
+            && self
+                .bigram_timings
+                .get(bigram)
+                .is_some_and(|stats| stats.raw.len() >= MIN_BIGRAM_SAMPLES)
     }
 
     /// Add a line of completed training. Optionally returns a new char set.
@@ -171,36 +570,77 @@ impl State {
         &mut self,
         line: Line,
         layout: &Layout,
-        difficulty: &Difficulty,
+        language: Language,
+        targets: &Targets,
     ) -> Option<CharSet> {
+        let record = LineRecord::new(&line, self.char_set.len());
+        self.totals.add(&record);
+        self.history.push(record);
+
+        let recording = Recording {
+            time: line.time,
+            text: line.text.clone(),
+            strokes: line.strokes.clone(),
+        };
+        // A best saved before short lines were left out is replaced by the
+        // next line that counts
+        let best_wpm = self.best().and_then(Recording::wpm);
+        if let Some(wpm) = recording.wpm() {
+            if recording.text.chars().count() >= MIN_BEST_CHARS
+                && best_wpm.is_none_or(|best| best < wpm)
+            {
+                self.best = Some(recording.clone());
+            }
+        }
+        self.recordings.push(recording);
+        let excess = self.recordings.len().saturating_sub(MAX_RECORDINGS);
+        self.recordings.drain(..excess);
+
+        let alpha = targets.clean_alpha();
         for hit in line.hits.iter().skip(1) {
             if hit.misses.is_empty() {
-                self.timings.entry(hit.target).or_default().push(hit.dt);
+                self.timings
+                    .entry(hit.target)
+                    .or_default()
+                    .push(hit.dt, line.time);
             }
 
             let clean_signal = if hit.misses.is_empty() { 1.0 } else { 0.0 };
             if let Some(clean) = self.clean.get_mut(&hit.target) {
-                *clean = clean_signal * CLEAN_ALPHA_COEFF + *clean * (1.0 - CLEAN_ALPHA_COEFF);
+                *clean = clean_signal * alpha + *clean * (1.0 - alpha);
             } else {
-                self.clean
-                    .insert(hit.target, clean_signal * CLEAN_ALPHA_COEFF);
+                self.clean.insert(hit.target, clean_signal * alpha);
+            }
+
+            if let Some(bigram) = hit.bigram() {
+                if hit.misses.is_empty() {
+                    self.bigram_timings
+                        .entry(bigram)
+                        .or_default()
+                        .push(hit.dt, line.time);
+                }
+                let clean = self.bigram_clean.entry(bigram).or_insert(0.0);
+                *clean = clean_signal * alpha + *clean * (1.0 - alpha);
             }
         }
         self.timings
-            .iter_mut()
-            .for_each(|(_, stats)| stats.recompute());
+            .values_mut()
+            .chain(self.bigram_timings.values_mut())
+            .for_each(|stats| stats.recompute(line.time));
 
-        let all_clean = self.clean.iter().all(|(_, &v)| v >= MIN_CLEAN_PCT);
-        let all_fast_enough = self
-            .timings
-            .iter()
-            .all(|(_, stats)| stats.wpm_harmonic_mean >= difficulty.words_per_minute());
+        if self
+            .totals
+            .lines_typed
+            .is_multiple_of(PROGRESS_INTERVAL_LINES)
+        {
+            self.record_progress(line.time);
+        }
 
-        if all_clean && all_fast_enough {
-            if let Some(letter) = layout.next_char(&self.char_set) {
+        if self.meets(targets) {
+            if let Some(&letter) = self.unlock_order(layout, language).first() {
                 self.char_set.insert(letter);
                 self.clean.insert(letter, 0.0);
-                self.events.push(Event::unlock(letter));
+                self.events.push(Event::unlock(letter, line.time, false));
 
                 return Some(self.char_set.clone());
             }
@@ -209,6 +649,161 @@ impl State {
         None
     }
 
+    /// Letters still to unlock, in the order they will be unlocked: any put
+    /// first by hand, then the rest of the layout's order
+    pub fn unlock_order(&self, layout: &Layout, language: Language) -> Vec<char> {
+        self.order
+            .iter()
+            .cloned()
+            .chain(layout.letter_order(language))
+            .filter(|letter| !self.char_set.contains(letter))
+            .unique()
+            .collect()
+    }
+
+    /// Add a letter to the training set by hand. Any statistics it already
+    /// has are kept. Returns whether the set changed.
+    pub fn unlock(&mut self, letter: char, time: OffsetDateTime) -> bool {
+        if letter == ' ' || !self.char_set.insert(letter) {
+            return false;
+        }
+        self.clean.entry(letter).or_insert(0.0);
+        self.order.retain(|&c| c != letter);
+        self.events.push(Event::unlock(letter, time, true));
+
+        true
+    }
+
+    /// Add every letter still to unlock to the training set
+    pub fn unlock_all(&mut self, layout: &Layout, language: Language, time: OffsetDateTime) {
+        for letter in self.unlock_order(layout, language) {
+            self.unlock(letter, time);
+        }
+    }
+
+    /// Take a letter out of the training set by hand. It takes its place in
+    /// the unlock order again, and its statistics are kept for when it comes
+    /// back. The set never shrinks below the letters a new profile starts
+    /// with. Returns whether the set changed.
+    pub fn lock(&mut self, letter: char, time: OffsetDateTime) -> bool {
+        if self.char_set.len() <= INITIAL_CHARS || !self.char_set.remove(&letter) {
+            return false;
+        }
+        self.events.push(Event::Lock { letter, time });
+
+        true
+    }
+
+    /// Unlock these letters next, in this order, ahead of the rest of the
+    /// layout's order. Letters already unlocked, or not on the layout, are
+    /// ignored.
+    pub fn reorder(
+        &mut self,
+        first: &[char],
+        layout: &Layout,
+        language: Language,
+        time: OffsetDateTime,
+    ) {
+        let remaining = self.unlock_order(layout, language);
+        self.order = first
+            .iter()
+            .cloned()
+            .filter(|letter| remaining.contains(letter))
+            .unique()
+            .collect();
+        self.events.push(Event::Reorder {
+            order: self.unlock_order(layout, language),
+            time,
+        });
+    }
+
+    /// Whether every letter in training has reached both targets
+    pub fn meets(&self, targets: &Targets) -> bool {
+        let all_clean = self
+            .clean
+            .iter()
+            .filter(|(ch, _)| self.in_training(ch))
+            .all(|(_, &v)| v >= targets.accuracy);
+        let all_fast_enough = self
+            .timings
+            .iter()
+            .filter(|(ch, _)| self.in_training(ch))
+            .all(|(_, stats)| stats.wpm_harmonic_mean >= targets.wpm);
+
+        all_clean && all_fast_enough
+    }
+
+    /// Log a progress point, along with a checkpoint when one is due
+    fn record_progress(&mut self, time: OffsetDateTime) {
+        let recent = &self.history[self
+            .history
+            .len()
+            .saturating_sub(PROGRESS_INTERVAL_LINES as usize)..];
+        let recent_characters: u32 = recent.iter().map(|record| record.characters as u32).sum();
+        let recent_duration = recent
+            .iter()
+            .fold(Duration::zero(), |total, record| total + record.duration);
+        let average_speed_wpm = if recent_characters > 0 {
+            (recent_duration / recent_characters).into()
+        } else {
+            WordsPerMinute::default()
+        };
+
+        self.events.push(Event::Progress {
+            time,
+            total_time_training: self.totals.time_training,
+            total_lines_typed: self.totals.lines_typed,
+            total_characters_typed: self.totals.characters_typed,
+            top_speed_wpm: self.totals.top_speed_wpm.into(),
+            average_speed_wpm: average_speed_wpm.into(),
+            num_characters: self.char_set.len().min(u8::MAX as usize) as u8,
+        });
+
+        if self
+            .totals
+            .lines_typed
+            .is_multiple_of(PROGRESS_INTERVAL_LINES * CHECKPOINT_INTERVAL)
+        {
+            let letters = self
+                .timings
+                .iter()
+                .map(|(&letter, stats)| {
+                    let rollup = Rollup {
+                        wpm_mean: stats.wpm_mean,
+                        wpm_harmonic_mean: stats.wpm_harmonic_mean,
+                        clean: self.clean.get(&letter).cloned().unwrap_or_default(),
+                    };
+                    (letter, rollup)
+                })
+                .collect();
+            self.events.push(Event::Checkpoint { time, letters });
+        }
+    }
+
+    pub fn history(&self) -> &[LineRecord] {
+        &self.history
+    }
+
+    pub fn events(&self) -> &[Event] {
+        &self.events
+    }
+
+    /// Keystrokes of the most recent lines, oldest first
+    pub fn recordings(&self) -> &[Recording] {
+        &self.recordings
+    }
+
+    /// The fastest line typed, leaving out lines too short to race
+    pub fn best(&self) -> Option<&Recording> {
+        self.best
+            .as_ref()
+            .filter(|best| best.text.chars().count() >= MIN_BEST_CHARS)
+    }
+
+    pub fn totals(&self) -> &Totals {
+        &self.totals
+    }
+
     pub fn clean_letters(&self) -> Vec<(char, f32)> {
         use std::cmp::Ordering;
         self.clean
@@ -219,6 +814,79 @@ impl State {
     }
 }
 
+impl Bigram {
+    /// Whether the word contains this transition
+    pub fn in_word(&self, word: &str) -> bool {
+        word.chars()
+            .tuple_windows()
+            .any(|(a, b)| a == self.0 && b == self.1)
+    }
+}
+
+impl std::fmt::Display for Bigram {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", self.0, self.1)
+    }
+}
+
+impl From<Bigram> for String {
+    fn from(bigram: Bigram) -> String {
+        bigram.to_string()
+    }
+}
+
+impl TryFrom<String> for Bigram {
+    type Error = &'static str;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.chars()
+            .collect_tuple()
+            .map(|(a, b)| Bigram(a, b))
+            .ok_or("bigram must be exactly two characters")
+    }
+}
+
+impl Totals {
+    fn add(&mut self, record: &LineRecord) {
+        self.time_training += record.duration;
+        self.lines_typed += 1;
+        self.characters_typed += record.characters as u64;
+        if record.wpm > self.top_speed_wpm {
+            self.top_speed_wpm = record.wpm;
+        }
+    }
+}
+
+impl LineRecord {
+    fn new(line: &Line, unlocked: usize) -> Self {
+        // The first hit includes any pause before the line started
+        let hits = || line.hits.iter().skip(1);
+        let characters = hits().count();
+        let duration = hits().fold(Duration::zero(), |total, hit| total + hit.dt);
+        let clean = hits().filter(|hit| !hit.is_dirty()).count();
+        let errors = hits().map(|hit| hit.misses.len()).sum::<usize>();
+
+        let (wpm, accuracy) = if characters > 0 {
+            (
+                (duration / characters as u32).into(),
+                clean as f32 / characters as f32,
+            )
+        } else {
+            (WordsPerMinute::default(), 0.0)
+        };
+
+        Self {
+            time: line.time,
+            duration,
+            characters: characters as u16,
+            errors: errors as u16,
+            wpm,
+            accuracy,
+            unlocked: unlocked as u16,
+        }
+    }
+}
+
 impl From<Duration> for WordsPerMinute {
     fn from(duration: Duration) -> Self {
         let seconds_per_character = duration.as_seconds_f64();
@@ -246,15 +914,24 @@ impl Difficulty {
         Difficulty::Casual,
         Difficulty::Normal,
         Difficulty::Strict,
+        Difficulty::Custom,
     ];
 
-    pub fn words_per_minute(&self) -> WordsPerMinute {
-        match self {
-            Difficulty::Easy => WordsPerMinute(5.0),
-            Difficulty::Casual => WordsPerMinute(15.0),
-            Difficulty::Normal => WordsPerMinute(30.0),
-            Difficulty::Strict => WordsPerMinute(45.0),
-        }
+    /// Targets of a preset difficulty, or `None` when the profile sets its own
+    pub fn preset(&self) -> Option<Targets> {
+        let wpm = match self {
+            Difficulty::Easy => 5.0,
+            Difficulty::Casual => 15.0,
+            Difficulty::Normal => 30.0,
+            Difficulty::Strict => 45.0,
+            Difficulty::Custom => return None,
+        };
+
+        Some(Targets {
+            wpm: WordsPerMinute(wpm),
+            accuracy: MIN_CLEAN_PCT,
+            auto_raise: false,
+        })
     }
 }
 
@@ -265,54 +942,162 @@ impl std::fmt::Display for Difficulty {
             Difficulty::Casual => "Casual",
             Difficulty::Normal => "Normal",
             Difficulty::Strict => "Strict",
+            Difficulty::Custom => "Custom",
         };
 
-        write!(f, "{} ({} wpm)", s, f64::from(self.words_per_minute()))
+        match self.preset() {
+            Some(targets) => write!(f, "{} ({} wpm)", s, f64::from(targets.wpm)),
+            None => write!(f, "{}", s),
+        }
+    }
+}
+
+impl Targets {
+    /// Targets within the allowed ranges
+    pub fn new(wpm: f64, accuracy: f32, auto_raise: bool) -> Self {
+        Self {
+            wpm: WordsPerMinute(wpm.clamp(*TARGET_WPM_RANGE.start(), *TARGET_WPM_RANGE.end())),
+            accuracy: accuracy.clamp(*TARGET_ACCURACY_RANGE.start(), *TARGET_ACCURACY_RANGE.end()),
+            auto_raise,
+        }
+    }
+
+    /// The speed target raised by one step
+    pub fn raised(&self) -> Self {
+        Self::new(self.wpm.0 + RAISE_WPM, self.accuracy, self.auto_raise)
+    }
+
+    /// Weight of each new hit in the clean proportions. A stricter target
+    /// averages over more hits, so that one miss does not drop a letter
+    /// that meets it below it; the default target gives 1/11.
+    pub fn clean_alpha(&self) -> f32 {
+        let window = CLEAN_WINDOW_MISSES / (1.0 - self.accuracy);
+        1.0 / (1.0 + window.min(MAX_CLEAN_WINDOW))
+    }
+
+    /// Scale for colouring accuracy, centred on the target
+    pub fn accuracy_metric(&self) -> TriplePoint {
+        let accuracy = self.accuracy;
+        TriplePoint::new(accuracy - 0.25, accuracy, accuracy + 0.9 * (1.0 - accuracy))
+            .unwrap_or_default()
+    }
+
+    /// Scale for colouring speed, centred on the target
+    pub fn wpm_metric(&self) -> TriplePoint {
+        let wpm = self.wpm.0 as f32;
+        TriplePoint::new(wpm / 3.0, wpm, 2.0 * wpm).unwrap_or_default()
+    }
+}
+
+/// The targets of the default difficulty, as a starting point
+impl Default for Targets {
+    fn default() -> Self {
+        Self::new(30.0, MIN_CLEAN_PCT, false)
     }
 }
 
 impl Stats {
-    pub fn push(&mut self, duration: Duration) {
+    pub fn push(&mut self, duration: Duration, time: OffsetDateTime) {
         self.raw.push(duration.into());
+        self.times.push(time);
         while self.raw.len() > NUM_RECENT_TIMINGS {
             self.raw.remove(0);
         }
+        let excess = self.times.len().saturating_sub(self.raw.len());
+        self.times.drain(..excess);
     }
 
-    pub fn recompute(&mut self) {
-        let mut raw: Vec<f64> = self.raw.iter().map(move |&v| f64::from(v)).collect();
-        let data = statistics::Data::new(raw.as_mut_slice());
-        self.wpm_mean = round(data.mean().unwrap_or_default(), 2).into();
-        self.wpm_harmonic_mean = round(raw.harmonic_mean(), 2).into();
+    /// Recompute the means, weighting each sample by how long before `now`
+    /// it was typed, so that recent samples count for more. Undated samples
+    /// count as old as the oldest dated one.
+    pub fn recompute(&mut self, now: OffsetDateTime) {
+        if self.raw.is_empty() {
+            return;
+        }
+
+        let oldest = self.times.first().cloned();
+        let undated = self.raw.len() - self.times.len();
+        let weights = (0..self.raw.len()).map(|i| {
+            let time = if i < undated {
+                oldest
+            } else {
+                Some(self.times[i - undated])
+            };
+            let age = match time {
+                Some(time) => (now - time).as_seconds_f64().max(0.0) / 86_400.0,
+                None => 0.0,
+            };
+            0.5_f64.powf(age / TIMING_HALF_LIFE_DAYS)
+        });
+
+        let (mut total, mut weighted, mut inverse) = (0.0, 0.0, 0.0);
+        for (weight, wpm) in weights.zip(self.raw.iter().map(|&wpm| f64::from(wpm))) {
+            total += weight;
+            weighted += weight * wpm;
+            inverse += weight / wpm;
+        }
+        if total == 0.0 {
+            self.wpm_mean = WordsPerMinute::default();
+            self.wpm_harmonic_mean = WordsPerMinute::default();
+            return;
+        }
+        self.wpm_mean = round(weighted / total, 2).into();
+        self.wpm_harmonic_mean = round(total / inverse, 2).into();
+    }
+
+    /// When the letter was last typed cleanly, if known
+    pub fn last_practised(&self) -> Option<OffsetDateTime> {
+        self.times.last().cloned()
     }
 }
 
 impl Event {
-    fn unlock(letter: char) -> Self {
+    fn unlock(letter: char, time: OffsetDateTime, manual: bool) -> Self {
         Self::Unlock {
             letter,
-            time: OffsetDateTime::now_utc(),
+            time,
+            manual,
         }
     }
 }
 
 impl Session {
-    pub fn new(setting: &words::Setting, state: &State) -> Self {
-        let mut words = setting.get_words(state.char_set());
-        let least_accurate = state.needs_improvement(1);
-        let line = words.line(CHARS_PER_LINE, &least_accurate);
+    /// Start a session whose first `SEEDED_LINES` lines are generated up
+    /// front from `seed`. They depend only on the seed, the words and the
+    /// letters in training, not on statistics that change as lines are typed,
+    /// so the same seed and letters always give the same lines.
+    pub fn new(
+        setting: &words::Setting,
+        state: &State,
+        layout: &Layout,
+        language: Language,
+        seed: u64,
+    ) -> Self {
+        let mut words = setting.get_words(state.char_set(), language);
+        let mut rng = StdRng::seed_from_u64(seed);
+        let fingers = layout.fingers();
+        let mut seeded: VecDeque<String> = (0..SEEDED_LINES)
+            .map(|_| words.line(CHARS_PER_LINE, &CharSet::new(), &[], &mut rng))
+            .collect();
+        let line = seeded.pop_front().unwrap_or_default();
 
         let mut targets: VecDeque<char> = line.chars().collect::<Vec<char>>().into();
         let first_letter = targets.pop_front().unwrap_or(' ');
 
-        let mut next_lines = Vec::new();
-        while next_lines.len() < NEXT_LINES {
-            next_lines.push(words.line(CHARS_PER_LINE, &least_accurate));
-        }
+        let next_lines = seeded.drain(..NEXT_LINES.min(seeded.len())).collect();
 
+        let clock = Clock::system();
         Self {
             words,
-            baseline: Instant::now(),
+            seed,
+            rng,
+            seeded,
+            fingers,
+            baseline: clock.elapsed(),
+            line_start: clock.elapsed(),
+            line_text: line,
+            strokes: Vec::new(),
+            clock,
             active_hit: Hit::new(first_letter, ' '),
             targets,
             hits: Vec::new(),
@@ -322,17 +1107,34 @@ impl Session {
     }
 
     pub fn apply_char(&mut self, c: char) -> Option<Line> {
+        self.record(Key::Char(c));
+
+        // Tab at the start of a line of code skips over the indentation
+        if c == '\t' && self.at_indentation() {
+            while self.targets.front() == Some(&' ') {
+                self.targets.pop_front();
+            }
+            return self.hit(' ');
+        }
+
+        self.hit(c)
+    }
+
+    fn hit(&mut self, c: char) -> Option<Line> {
         if self.errors.is_empty() && c == self.active_hit.target {
-            self.active_hit.finalize(self.baseline);
+            let now = self.clock.elapsed();
+            self.active_hit.finalize(now - self.baseline);
             self.hits.push(self.active_hit.clone());
-            self.baseline = Instant::now();
+            self.baseline = now;
 
             if let Some(next_target) = self.targets.pop_front() {
                 self.active_hit = self.active_hit.next(next_target);
             } else {
                 let line = Line {
                     hits: self.hits.clone(),
-                    time: OffsetDateTime::now_utc(),
+                    text: std::mem::take(&mut self.line_text),
+                    strokes: std::mem::take(&mut self.strokes),
+                    time: self.clock.now_utc(),
                 };
                 self.hits.clear();
 
@@ -349,16 +1151,42 @@ impl Session {
         None
     }
 
-    pub fn fill_next_lines(&mut self, state: &State) {
-        let least_accurate = state.needs_improvement(1);
+    fn record(&mut self, key: Key) {
+        if self.strokes.is_empty() {
+            self.line_start = self.clock.elapsed();
+        }
+        let elapsed = self.clock.elapsed() - self.line_start;
+        self.strokes.push(Stroke::new(key, elapsed));
+    }
+
+    /// Whether everything typed so far on this line, and the current target,
+    /// is leading whitespace
+    fn at_indentation(&self) -> bool {
+        self.errors.is_empty()
+            && self.active_hit.target == ' '
+            && self.hits.iter().all(|hit| hit.target == ' ')
+    }
+
+    pub fn fill_next_lines(&mut self, state: &State, targets: &Targets) {
+        let least_accurate = state.focus(&self.fingers, self.clock.now_utc(), targets);
+        let weak_bigrams = state.weak_bigrams(NUM_WEAK_BIGRAMS, targets);
         while self.next_lines.len() < NEXT_LINES + 1 {
+            if let Some(line) = self.seeded.pop_front() {
+                self.next_lines.push(line);
+                continue;
+            }
             // TODO: Weighted character set selection:
             // - More words with characters that are our lower hit percentage
             // - Characters that are our slowest
-            self.next_lines
-                .push(self.words.line(CHARS_PER_LINE, &least_accurate));
+            self.next_lines.push(self.words.line(
+                CHARS_PER_LINE,
+                &least_accurate,
+                &weak_bigrams,
+                &mut self.rng,
+            ));
         }
-        for c in self.next_lines.remove(0).chars() {
+        self.line_text = self.next_lines.remove(0);
+        for c in self.line_text.chars() {
             self.targets.push_back(c);
         }
 
@@ -367,18 +1195,49 @@ impl Session {
     }
 
     pub fn backspace(&mut self) {
+        self.record(Key::Backspace);
         if !self.errors.is_empty() {
             self.errors.pop();
         }
     }
 
+    /// Draw lines from other words. Any seeded lines left were made from the
+    /// old words, so lines adapt to the training state from here on.
     pub fn update_words(&mut self, words: Words) {
         self.words = words;
+        self.seeded.clear();
     }
 
     pub fn words_setting(&self) -> words::Setting {
         (&self.words).into()
     }
+
+    /// Time keystrokes by another clock, such as a manual one for simulations
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.baseline = clock.elapsed();
+        self.line_start = self.baseline;
+        self.clock = clock;
+        self
+    }
+
+    pub fn clock(&self) -> &Clock {
+        &self.clock
+    }
+
+    pub fn clock_mut(&mut self) -> &mut Clock {
+        &mut self.clock
+    }
+
+    /// Time since the first key of the current line, once one is pressed
+    pub fn line_elapsed(&self) -> Option<Duration> {
+        (!self.strokes.is_empty()).then(|| self.clock.elapsed() - self.line_start)
+    }
+
+    /// Seed to share or replay to get this session's first `SEEDED_LINES`
+    /// lines again
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
 }
 
 impl Hit {
@@ -401,8 +1260,9 @@ impl Hit {
         self.misses.insert(miss);
     }
 
-    pub fn finalize(&mut self, baseline: Instant) {
-        self.dt = baseline.elapsed();
+    /// Record how long the hit took since the previous one
+    pub fn finalize(&mut self, dt: Duration) {
+        self.dt = dt;
         if self.dt.whole_nanoseconds() > Self::MAX_DURATION_NS as i128 {
             self.dt = Duration::nanoseconds(Self::MAX_DURATION_NS);
         }
@@ -412,6 +1272,13 @@ impl Hit {
         self.target
     }
 
+    /// The transition from the previous character, ignoring spaces and line
+    /// breaks
+    pub fn bigram(&self) -> Option<Bigram> {
+        (!self.prev.is_whitespace() && !self.target.is_whitespace())
+            .then_some(Bigram(self.prev, self.target))
+    }
+
     pub fn is_dirty(&self) -> bool {
         !self.misses.is_empty()
     }
@@ -444,7 +1311,199 @@ impl TriplePoint {
     }
 }
 
+/// Printable stand-in for line breaks and tabs in source code
+pub fn visible(c: char) -> char {
+    match c {
+        '\n' => '\u{23ce}',
+        '\t' => '\u{21e5}',
+        c => c,
+    }
+}
+
+/// Order letters and fingers from weakest to strongest: any below the
+/// accuracy target by accuracy, then the rest by speed
+fn weakness(clean: f32, wpm: WordsPerMinute, targets: &Targets) -> f64 {
+    if clean < targets.accuracy {
+        f64::from(clean) - 1.0
+    } else {
+        f64::from(wpm)
+    }
+}
+
 fn round(n: f64, places: i32) -> f64 {
     let factor = 10.0_f64.powi(places);
     (n * factor).round() / factor
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A line typed one key every `ms` milliseconds, without mistakes
+    fn line(text: &str, ms: i64) -> Line {
+        Line {
+            hits: Vec::new(),
+            text: text.to_string(),
+            strokes: text
+                .chars()
+                .enumerate()
+                .map(|(i, c)| Stroke::new(Key::Char(c), Duration::milliseconds(i as i64 * ms)))
+                .collect(),
+            time: OffsetDateTime::unix_epoch(),
+        }
+    }
+
+    fn add(state: &mut State, line: Line) {
+        state.add_line(
+            line,
+            &Layout::default(),
+            Language::English,
+            &Targets::default(),
+        );
+    }
+
+    #[test]
+    fn one_key_line_has_no_speed() {
+        let recording = Recording {
+            time: OffsetDateTime::unix_epoch(),
+            text: "}".to_string(),
+            strokes: vec![Stroke::new(Key::Char('}'), Duration::zero())],
+        };
+
+        assert_eq!(recording.wpm(), None);
+    }
+
+    #[test]
+    fn short_lines_are_not_the_best() {
+        let mut state = State::new(vec!['a', 's', 'd', 'f', 'e', 'j']);
+        add(&mut state, line("}", 100));
+        add(&mut state, line("as", 10));
+        assert!(state.best().is_none());
+
+        add(&mut state, line("sad fads ease", 200));
+        add(&mut state, line("jade fades as", 100));
+        add(&mut state, line("sea", 1));
+        assert_eq!(state.best().unwrap().text, "jade fades as");
+    }
+
+    #[test]
+    fn stuck_best_is_replaced() {
+        let mut state = State::new(vec!['a', 's', 'd', 'f', 'e', 'j']);
+        let stuck = line("}", 100);
+        state.best = Some(Recording {
+            time: stuck.time,
+            text: stuck.text,
+            strokes: stuck.strokes,
+        });
+        assert!(state.best().is_none());
+
+        add(&mut state, line("sad fads ease", 200));
+        assert_eq!(state.best().unwrap().text, "sad fads ease");
+    }
+
+    #[test]
+    fn old_samples_keep_their_speed() {
+        let typed = OffsetDateTime::unix_epoch();
+        let mut stats = Stats::default();
+        for _ in 0..4 {
+            stats.push(Duration::milliseconds(200), typed);
+        }
+
+        stats.recompute(typed);
+        let fresh = stats.wpm_harmonic_mean;
+        stats.recompute(typed + Duration::days(28));
+
+        assert_eq!(stats.wpm_harmonic_mean, fresh);
+    }
+
+    #[test]
+    fn recent_samples_count_for_more() {
+        let start = OffsetDateTime::unix_epoch();
+        let now = start + Duration::days(7);
+        let mut stats = Stats::default();
+        stats.push(Duration::milliseconds(400), start);
+        stats.push(Duration::milliseconds(200), now);
+        stats.recompute(now);
+
+        // 30 wpm at half weight and 60 wpm at full weight
+        assert_eq!(f64::from(stats.wpm_mean), 50.0);
+        assert_eq!(f64::from(stats.wpm_harmonic_mean), 45.0);
+    }
+
+    #[test]
+    fn letters_go_stale_while_away() {
+        let start = OffsetDateTime::unix_epoch();
+        let mut state = State::new(vec!['a', 's', 'd', 'f', 'e', 'j']);
+        for (letter, days) in [('a', 0), ('s', 2)] {
+            let stats = state.timings.get_mut(&letter).unwrap();
+            stats.push(Duration::milliseconds(200), start + Duration::days(days));
+        }
+
+        assert!(state.due_for_review(start + Duration::days(2)).is_empty());
+        assert_eq!(
+            state.due_for_review(start + Duration::days(4)),
+            [('a', Duration::days(4))]
+        );
+    }
+
+    #[test]
+    fn fingers_and_hands_average_their_letters() {
+        let now = OffsetDateTime::unix_epoch();
+        let mut state = State::new(vec!['a', 's', 'd', 'f', 'e', 'j']);
+        // 60 wpm and 40 wpm, both pressed by the left middle finger on QWERTY
+        for (letter, ms, clean) in [('d', 200, 1.0), ('e', 300, 0.5)] {
+            let stats = state.timings.get_mut(&letter).unwrap();
+            stats.push(Duration::milliseconds(ms), now);
+            stats.recompute(now);
+            state.clean.insert(letter, clean);
+        }
+        state.recordings.push(Recording {
+            time: now,
+            text: "ddj".to_string(),
+            strokes: Vec::new(),
+        });
+        let fingers = Layout::default().fingers();
+
+        let middle = state
+            .finger_stats(&fingers)
+            .into_iter()
+            .find(|stats| stats.finger == Finger::LeftMiddle)
+            .unwrap();
+        assert_eq!(middle.letters, ['d', 'e']);
+        assert_eq!(f64::from(middle.wpm), 50.0);
+        assert_eq!(middle.clean, 0.75);
+
+        let hands = state.hand_stats(&fingers);
+        assert_eq!(hands[0].hand, Hand::Left);
+        assert_eq!(f64::from(hands[0].wpm), 50.0);
+        assert_eq!(hands[0].share, 2.0 / 3.0);
+        assert_eq!(hands[1].share, 1.0 / 3.0);
+    }
+
+    #[test]
+    fn accuracy_target_picks_the_focus() {
+        let now = OffsetDateTime::unix_epoch();
+        let mut state = State::new(vec!['a', 's', 'd', 'f', 'e', 'j']);
+        for letter in ['a', 's', 'd', 'f', 'e', 'j', ' '] {
+            state.clean.insert(letter, 0.95);
+        }
+        state.clean.insert('j', 0.8);
+        let strict = Targets::new(30.0, 0.9, false);
+
+        assert_ne!(
+            state.needs_improvement(1, now, &Targets::default()),
+            CharSet::from_iter(['j'])
+        );
+        assert_eq!(
+            state.needs_improvement(1, now, &strict),
+            CharSet::from_iter(['j'])
+        );
+    }
+
+    #[test]
+    fn stricter_targets_smooth_over_more_hits() {
+        assert_eq!(Targets::default().clean_alpha(), 1.0 / 11.0);
+        assert_eq!(Targets::new(30.0, 0.875, false).clean_alpha(), 1.0 / 21.0);
+        assert_eq!(Targets::new(30.0, 0.99, false).clean_alpha(), 1.0 / 51.0);
+    }
+}
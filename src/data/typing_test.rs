@@ -0,0 +1,417 @@
+use super::clock::Clock;
+use super::keyboard::Layout;
+use super::language::Language;
+use super::training::{WordsPerMinute, CHARS_PER_LINE};
+use super::words::Words;
+use super::CharSet;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
+use time::{Duration, OffsetDateTime};
+
+/// Characters counted as one word in the standard speed formulas
+const CHARS_PER_WORD: f64 = 5.0;
+
+/// How long a typing test runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Length {
+    /// Type for a number of seconds
+    Seconds(u16),
+    /// Type a number of words
+    Words(u16),
+}
+
+pub const LENGTHS: &[Length] = &[
+    Length::Seconds(15),
+    Length::Seconds(30),
+    Length::Seconds(60),
+    Length::Seconds(120),
+    Length::Words(10),
+    Length::Words(25),
+    Length::Words(50),
+    Length::Words(100),
+];
+
+/// A typing test in progress. Unlike training, mistakes do not block the
+/// way: they stay in the text unless deleted, and count against the net
+/// speed at the end.
+#[derive(Debug, Clone)]
+pub struct Test {
+    length: Length,
+    words: Words,
+    rng: StdRng,
+    clock: Clock,
+    /// Time on the clock when the first key was pressed
+    started: Option<Duration>,
+    /// Text to type, extended as the end comes near in a timed test
+    text: Vec<char>,
+    /// Characters typed so far, right or wrong
+    typed: Vec<char>,
+    /// Every key pressed, including those later deleted
+    keystrokes: u32,
+    /// Keys pressed that did not match the text
+    mistakes: u32,
+    /// Set once the test is over
+    score: Option<Score>,
+}
+
+/// Result of a completed typing test
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Score {
+    /// When the test was completed
+    pub time: OffsetDateTime,
+    pub length: Length,
+    /// Time from the first key to the end of the test
+    pub duration: Duration,
+    /// Characters left in the text at the end, right or wrong
+    pub characters: u32,
+    /// Characters left wrong at the end
+    pub errors: u32,
+    /// Every key pressed, including those later deleted
+    pub keystrokes: u32,
+    /// Keys pressed that did not match the text
+    pub mistakes: u32,
+}
+
+impl Test {
+    /// Start a test with words from every letter of the layout. The test
+    /// only starts timing once the first key is pressed.
+    pub fn new(length: Length, layout: &Layout, language: Language, seed: u64) -> Self {
+        let char_set: CharSet = layout
+            .letter_order(language)
+            .into_iter()
+            .filter(|c| c.is_lowercase())
+            .chain(std::iter::once(' '))
+            .collect();
+
+        let mut test = Self {
+            length,
+            words: Words::dictionary(char_set, language),
+            rng: StdRng::seed_from_u64(seed),
+            clock: Clock::system(),
+            started: None,
+            text: Vec::new(),
+            typed: Vec::new(),
+            keystrokes: 0,
+            mistakes: 0,
+            score: None,
+        };
+        match length {
+            Length::Seconds(_) => test.extend_text(),
+            Length::Words(count) => {
+                let mut words = Vec::new();
+                while words.len() < count as usize {
+                    let line = test.next_line();
+                    if line.trim().is_empty() {
+                        break;
+                    }
+                    words.extend(line.split_whitespace().map(str::to_string));
+                }
+                words.truncate(count as usize);
+                test.text = words.join(" ").chars().collect();
+            }
+        }
+
+        test
+    }
+
+    /// Time the test by another clock, such as a manual one for simulations
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn clock_mut(&mut self) -> &mut Clock {
+        &mut self.clock
+    }
+
+    /// Type a character. Returns the score once this completes the test,
+    /// including when time ran out before the key was pressed.
+    pub fn apply_char(&mut self, c: char) -> Option<Score> {
+        if self.score.is_some() {
+            return None;
+        }
+        if let Some(score) = self.tick() {
+            return Some(score);
+        }
+        let target = *self.text.get(self.typed.len())?;
+
+        self.started.get_or_insert_with(|| self.clock.elapsed());
+        self.keystrokes += 1;
+        if c != target {
+            self.mistakes += 1;
+        }
+        self.typed.push(c);
+
+        match self.length {
+            Length::Seconds(_) => {
+                self.extend_text();
+                None
+            }
+            Length::Words(_) if self.typed.len() == self.text.len() => self.finish(),
+            Length::Words(_) => None,
+        }
+    }
+
+    /// Delete the last character typed
+    pub fn backspace(&mut self) {
+        if self.score.is_none() {
+            self.typed.pop();
+        }
+    }
+
+    /// Check the time, ending a timed test once it has run out. Returns the
+    /// score when this ends the test.
+    pub fn tick(&mut self) -> Option<Score> {
+        match self.length.duration() {
+            Some(duration) if self.score.is_none() && self.elapsed() >= duration => self.finish(),
+            _ => None,
+        }
+    }
+
+    pub fn length(&self) -> Length {
+        self.length
+    }
+
+    pub fn text(&self) -> &[char] {
+        &self.text
+    }
+
+    pub fn typed(&self) -> &[char] {
+        &self.typed
+    }
+
+    /// Whether the test has begun, once the first key is pressed
+    pub fn is_running(&self) -> bool {
+        self.started.is_some() && self.score.is_none()
+    }
+
+    pub fn score(&self) -> Option<&Score> {
+        self.score.as_ref()
+    }
+
+    /// Time since the first key was pressed
+    pub fn elapsed(&self) -> Duration {
+        match (&self.score, self.started) {
+            (Some(score), _) => score.duration,
+            (None, Some(started)) => self.clock.elapsed() - started,
+            (None, None) => Duration::zero(),
+        }
+    }
+
+    /// Time left in a timed test
+    pub fn remaining(&self) -> Option<Duration> {
+        self.length
+            .duration()
+            .map(|duration| (duration - self.elapsed()).max(Duration::zero()))
+    }
+
+    /// Number of words typed so far, counting the one in progress
+    pub fn words_typed(&self) -> usize {
+        self.typed
+            .iter()
+            .collect::<String>()
+            .split_whitespace()
+            .count()
+    }
+
+    fn finish(&mut self) -> Option<Score> {
+        let duration = self.length.duration().unwrap_or_else(|| self.elapsed());
+        let errors = self
+            .typed
+            .iter()
+            .zip(self.text.iter())
+            .filter(|(typed, target)| typed != target)
+            .count();
+        let score = Score {
+            time: self.clock.now_utc(),
+            length: self.length,
+            duration,
+            characters: self.typed.len() as u32,
+            errors: errors as u32,
+            keystrokes: self.keystrokes,
+            mistakes: self.mistakes,
+        };
+        self.score = Some(score.clone());
+
+        Some(score)
+    }
+
+    /// Keep a few lines of text ahead of the typist
+    fn extend_text(&mut self) {
+        while self.text.len() < self.typed.len() + 3 * CHARS_PER_LINE {
+            let line = self.next_line();
+            if line.trim().is_empty() {
+                break;
+            }
+            if !self.text.is_empty() {
+                self.text.push(' ');
+            }
+            self.text.extend(line.trim().chars());
+        }
+    }
+
+    fn next_line(&mut self) -> String {
+        self.words
+            .line(CHARS_PER_LINE, &CharSet::new(), &[], &mut self.rng)
+    }
+}
+
+impl Score {
+    /// Speed counting every character typed, right or wrong
+    pub fn gross_wpm(&self) -> WordsPerMinute {
+        (self.characters as f64 / CHARS_PER_WORD / self.minutes()).into()
+    }
+
+    /// Gross speed less one word a minute for each error left in the text
+    pub fn net_wpm(&self) -> WordsPerMinute {
+        let gross = f64::from(self.gross_wpm());
+        (gross - self.errors as f64 / self.minutes())
+            .max(0.0)
+            .into()
+    }
+
+    /// Proportion of keys pressed that matched the text, from 0 to 1
+    pub fn accuracy(&self) -> f32 {
+        if self.keystrokes == 0 {
+            return 0.0;
+        }
+        (self.keystrokes - self.mistakes) as f32 / self.keystrokes as f32
+    }
+
+    fn minutes(&self) -> f64 {
+        // A test cannot be shorter than a millisecond, which keeps the speed
+        // finite
+        self.duration.as_seconds_f64().max(0.001) / 60.0
+    }
+}
+
+impl Length {
+    /// Time allowed for a timed test
+    pub fn duration(&self) -> Option<Duration> {
+        match self {
+            Length::Seconds(seconds) => Some(Duration::seconds(*seconds as i64)),
+            Length::Words(_) => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Length {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Length::Seconds(seconds) => write!(f, "{} seconds", seconds),
+            Length::Words(words) => write!(f, "{} words", words),
+        }
+    }
+}
+
+impl Default for Length {
+    fn default() -> Self {
+        Length::Seconds(60)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn score(seconds: i64, characters: u32, errors: u32, keystrokes: u32, mistakes: u32) -> Score {
+        Score {
+            time: OffsetDateTime::unix_epoch(),
+            length: Length::Seconds(seconds as u16),
+            duration: Duration::seconds(seconds),
+            characters,
+            errors,
+            keystrokes,
+            mistakes,
+        }
+    }
+
+    #[test]
+    fn speeds_count_five_characters_a_word() {
+        // 150 characters in half a minute is 30 words, or 60 a minute. Five
+        // errors in half a minute take 10 off.
+        let score = score(30, 150, 5, 160, 8);
+
+        assert_eq!(f64::from(score.gross_wpm()), 60.0);
+        assert_eq!(f64::from(score.net_wpm()), 50.0);
+        assert_eq!(score.accuracy(), 0.95);
+    }
+
+    #[test]
+    fn net_speed_stops_at_zero() {
+        let score = score(60, 10, 10, 10, 10);
+
+        assert_eq!(f64::from(score.gross_wpm()), 2.0);
+        assert_eq!(f64::from(score.net_wpm()), 0.0);
+        assert_eq!(score.accuracy(), 0.0);
+    }
+
+    #[test]
+    fn no_keys_is_no_accuracy() {
+        assert_eq!(score(15, 0, 0, 0, 0).accuracy(), 0.0);
+    }
+
+    #[test]
+    fn word_tests_time_from_the_first_key_to_the_last() {
+        let clock = Clock::manual(OffsetDateTime::unix_epoch());
+        let mut test =
+            Test::new(Length::Words(3), &Layout::default(), Language::English, 7).with_clock(clock);
+        let text = test.text().to_vec();
+        // Waiting before the first key does not count
+        test.clock_mut().advance(Duration::seconds(5));
+
+        let mut score = None;
+        for (i, &target) in text.iter().enumerate() {
+            // One key wrong, then put right, and one left wrong
+            if i == 1 {
+                test.apply_char('#');
+                test.backspace();
+            }
+            let c = if i == 2 { '#' } else { target };
+            score = test.apply_char(c);
+            test.clock_mut().advance(Duration::milliseconds(100));
+        }
+        let score = score.unwrap();
+
+        // A key every 100 ms, from the first at 0 ms
+        assert_eq!(
+            score.duration,
+            Duration::milliseconds(100 * (text.len() as i64 - 1))
+        );
+        assert_eq!(score.characters, text.len() as u32);
+        assert_eq!(score.errors, 1);
+        assert_eq!(score.keystrokes, text.len() as u32 + 1);
+        assert_eq!(score.mistakes, 2);
+    }
+
+    #[test]
+    fn timed_tests_last_their_length() {
+        let clock = Clock::manual(OffsetDateTime::unix_epoch());
+        let mut test = Test::new(
+            Length::Seconds(15),
+            &Layout::default(),
+            Language::English,
+            7,
+        )
+        .with_clock(clock);
+        test.clock_mut().advance(Duration::seconds(5));
+        assert!(test.tick().is_none());
+
+        let text = test.text().to_vec();
+        for &c in &text[..10] {
+            test.apply_char(c);
+            test.clock_mut().advance(Duration::seconds(1));
+        }
+        assert_eq!(test.remaining(), Some(Duration::seconds(5)));
+        assert!(test.tick().is_none());
+
+        test.clock_mut().advance(Duration::seconds(10));
+        let score = test.tick().unwrap();
+        assert_eq!(score.duration, Duration::seconds(15));
+        assert_eq!(score.characters, 10);
+        assert_eq!(score.errors, 0);
+        // 10 characters in a quarter of a minute
+        assert_eq!(f64::from(score.gross_wpm()), 8.0);
+    }
+}
@@ -1,65 +1,273 @@
+use super::code::Code;
 use super::dictionary::Dictionary;
+use super::language::Language;
+use super::markov::{self, Markov};
 use super::random::Random;
+use super::training::Bigram;
 use super::CharSet;
+use rand::Rng;
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Arc;
 
-#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+#[derive(Debug, Clone, Default, Eq, PartialEq, Deserialize, Serialize)]
 pub enum Setting {
+    #[default]
     Dictionary,
     Random,
+    /// Pseudo-words made up by a model of a corpus
+    Markov(markov::Setting),
+    /// Words from a text file or word list on disk
+    File(PathBuf),
+    /// Lines of source code from a file or directory on disk
+    Code(PathBuf),
 }
 
-impl Default for Setting {
-    fn default() -> Self {
-        Self::Dictionary
-    }
+/// The kind of word source, without its details
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Source {
+    Dictionary,
+    Random,
+    Markov,
+    File,
+    Code,
 }
 
+pub const SOURCES: &[Source] = &[
+    Source::Dictionary,
+    Source::Random,
+    Source::Markov,
+    Source::File,
+    Source::Code,
+];
+
 impl Setting {
-    pub fn get_words(&self, char_set: CharSet) -> Words {
+    pub fn get_words(&self, char_set: CharSet, language: Language) -> Words {
+        match self {
+            Setting::Dictionary => Words::dictionary(char_set, language),
+            Setting::Random => Words::random(char_set, language),
+            Setting::Markov(setting) => Words::markov(setting.clone(), char_set, language),
+            Setting::File(path) => Words::file(path.clone(), char_set, language),
+            Setting::Code(path) => Words::code(path.clone(), char_set, language),
+        }
+    }
+
+    pub fn source(&self) -> Source {
         match self {
-            Setting::Dictionary => Words::dictionary(char_set),
-            Setting::Random => Words::random(char_set),
+            Setting::Dictionary => Source::Dictionary,
+            Setting::Random => Source::Random,
+            Setting::Markov(_) => Source::Markov,
+            Setting::File(_) => Source::File,
+            Setting::Code(_) => Source::Code,
         }
     }
 }
 
+impl std::fmt::Display for Source {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Source::Dictionary => "Dictionary",
+            Source::Random => "Random Letters",
+            Source::Markov => "Pseudo-words",
+            Source::File => "Text File",
+            Source::Code => "Source Code",
+        };
+
+        write!(f, "{}", s)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum Words {
     Dictionary {
         full: Dictionary,
-        char_limited: Dictionary,
+        char_limited: Limited,
     },
     Random {
         generator: Random,
         char_set: CharSet,
     },
+    Markov {
+        setting: markov::Setting,
+        model: Arc<Markov>,
+        char_set: CharSet,
+        /// Words used while none of the corpus letters are in the set
+        fallback: Option<Limited>,
+    },
+    File {
+        path: PathBuf,
+        full: Dictionary,
+        char_limited: Limited,
+    },
+    Code {
+        path: PathBuf,
+        code: Code,
+        /// Words used while no code could be read from the path
+        fallback: Option<Limited>,
+    },
+}
+
+/// Words made only of the characters in a set, or pseudo-words made from the
+/// set while no word is
+#[derive(Debug, Clone)]
+pub enum Limited {
+    Words(Dictionary),
+    Random {
+        generator: Random,
+        char_set: CharSet,
+    },
 }
 
 impl Words {
-    pub fn dictionary(char_set: CharSet) -> Self {
-        let dictionary = Dictionary::load();
-        let char_limited = dictionary.char_limited(&char_set);
+    pub fn dictionary(char_set: CharSet, language: Language) -> Self {
+        let dictionary = Dictionary::load(language);
+        let char_limited = char_limited(&dictionary, &char_set, language);
         Words::Dictionary {
             full: dictionary,
             char_limited,
         }
     }
 
-    pub fn random(char_set: CharSet) -> Self {
+    pub fn random(char_set: CharSet, language: Language) -> Self {
         Words::Random {
-            generator: Random::load(),
+            generator: Random::load(language),
+            char_set,
+        }
+    }
+
+    /// Pseudo-words from a model trained on the setting's corpus, falling
+    /// back to the built-in dictionary while none of its letters are in the
+    /// set
+    pub fn markov(setting: markov::Setting, char_set: CharSet, language: Language) -> Self {
+        let model = Markov::cached(&setting, language);
+        let fallback = (!model.fits(&char_set))
+            .then(|| char_limited(&Dictionary::load(language), &char_set, language));
+
+        Words::Markov {
+            model,
+            setting,
             char_set,
+            fallback,
+        }
+    }
+
+    /// Words from a text file, falling back to the built-in dictionary while
+    /// the file cannot be read or contains no words
+    pub fn file(path: PathBuf, char_set: CharSet, language: Language) -> Self {
+        let full = match std::fs::read_to_string(&path) {
+            Ok(text) => Some(Dictionary::from_text(&text)),
+            Err(error) => {
+                eprintln!("Could not read words from {}: {}", path.display(), error);
+                None
+            }
+        }
+        .filter(|dictionary| !dictionary.is_empty())
+        .unwrap_or_else(|| Dictionary::load(language));
+        let char_limited = char_limited(&full, &char_set, language);
+
+        Words::File {
+            path,
+            full,
+            char_limited,
+        }
+    }
+
+    /// Lines of source code, falling back to the built-in dictionary while
+    /// no code can be read from the path
+    pub fn code(path: PathBuf, char_set: CharSet, language: Language) -> Self {
+        let code = Code::load(&path);
+        let fallback = code
+            .is_empty()
+            .then(|| char_limited(&Dictionary::load(language), &char_set, language));
+
+        Words::Code {
+            path,
+            code,
+            fallback,
         }
     }
 
-    pub fn line(&mut self, length: usize, least_accurate: &CharSet) -> String {
+    /// Whether lines can be made from the source's own words using only the
+    /// characters in the set, rather than falling back to pseudo-words. Code
+    /// is typed as it is, whatever the set.
+    pub fn fits(&self, char_set: &CharSet) -> bool {
         match self {
-            Words::Dictionary { char_limited, .. } => char_limited.line(length, least_accurate),
+            Words::Dictionary { char_limited, .. } | Words::File { char_limited, .. } => {
+                char_limited.fits(char_set)
+            }
+            Words::Random { .. } => true,
+            Words::Markov {
+                fallback: Some(limited),
+                ..
+            } => limited.fits(char_set),
+            Words::Markov { model, .. } => model.fits(char_set),
+            Words::Code {
+                fallback: Some(limited),
+                ..
+            } => limited.fits(char_set),
+            Words::Code { .. } => true,
+        }
+    }
+
+    /// Next line of words, drawing every random choice from `rng`
+    pub fn line<R: Rng>(
+        &mut self,
+        length: usize,
+        least_accurate: &CharSet,
+        weak_bigrams: &[Bigram],
+        rng: &mut R,
+    ) -> String {
+        match self {
+            Words::Dictionary { char_limited, .. } | Words::File { char_limited, .. } => {
+                char_limited.line(length, least_accurate, weak_bigrams, rng)
+            }
             Words::Random {
                 generator,
                 char_set,
-            } => generator.line(char_set, length),
+            } => generator.line(char_set, length, least_accurate, rng),
+            Words::Markov {
+                fallback: Some(limited),
+                ..
+            } => limited.line(length, least_accurate, weak_bigrams, rng),
+            Words::Markov {
+                setting,
+                model,
+                char_set,
+                ..
+            } => model.line(char_set, setting.lengths, length, least_accurate, rng),
+            Words::Code {
+                fallback: Some(limited),
+                ..
+            } => limited.line(length, least_accurate, weak_bigrams, rng),
+            Words::Code { code, .. } => code.line(length, rng),
+        }
+    }
+}
+
+impl Limited {
+    /// Whether there are words using only the characters in the set
+    fn fits(&self, char_set: &CharSet) -> bool {
+        match self {
+            Limited::Words(dictionary) => dictionary.fits(char_set),
+            Limited::Random { .. } => false,
+        }
+    }
+
+    fn line<R: Rng>(
+        &self,
+        length: usize,
+        least_accurate: &CharSet,
+        weak_bigrams: &[Bigram],
+        rng: &mut R,
+    ) -> String {
+        match self {
+            Limited::Words(dictionary) => {
+                dictionary.line(length, least_accurate, weak_bigrams, rng)
+            }
+            Limited::Random {
+                generator,
+                char_set,
+            } => generator.line(char_set, length, least_accurate, rng),
         }
     }
 }
@@ -69,6 +277,94 @@ impl From<&Words> for Setting {
         match words {
             Words::Dictionary { .. } => Setting::Dictionary,
             Words::Random { .. } => Setting::Random,
+            Words::Markov { setting, .. } => Setting::Markov(setting.clone()),
+            Words::File { path, .. } => Setting::File(path.clone()),
+            Words::Code { path, .. } => Setting::Code(path.clone()),
         }
     }
 }
+
+/// Words using only characters in the set. While there are none, such as for
+/// a text file of unusual words or a few rare letters, fall back to the
+/// language's words limited in the same way, and then to pseudo-words made
+/// from the set, so that there is always something to type and it never
+/// needs a letter that is not unlocked.
+fn char_limited(full: &Dictionary, char_set: &CharSet, language: Language) -> Limited {
+    let limited = full.char_limited(char_set);
+    if !limited.is_empty() {
+        return Limited::Words(limited);
+    }
+
+    let limited = Dictionary::load(language).char_limited(char_set);
+    if !limited.is_empty() {
+        return Limited::Words(limited);
+    }
+
+    Limited::Random {
+        generator: Random::load(language),
+        char_set: char_set.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn temp_file(name: &str, contents: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("linkage-{}-{}", std::process::id(), name));
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn file_without_usable_words_falls_back() {
+        let path = temp_file("unusable.txt", "kubernetes pod container ingress helm");
+        let char_set: CharSet = "asdfej ".chars().collect();
+        let mut words = Words::file(path.clone(), char_set.clone(), Language::English);
+        let line = words.line(40, &CharSet::new(), &[], &mut StdRng::seed_from_u64(1));
+        std::fs::remove_file(path).unwrap();
+
+        assert!(!line.trim().is_empty());
+        assert!(line.chars().all(|c| char_set.contains(&c)), "{:?}", line);
+    }
+
+    #[test]
+    fn empty_file_falls_back() {
+        let path = temp_file("empty.txt", "");
+        let char_set: CharSet = "asdfej ".chars().collect();
+        let mut words = Words::file(path.clone(), char_set, Language::English);
+        let line = words.line(40, &CharSet::new(), &[], &mut StdRng::seed_from_u64(1));
+        std::fs::remove_file(path).unwrap();
+
+        assert!(!line.trim().is_empty());
+    }
+
+    #[test]
+    fn corpus_without_unlocked_letters_falls_back() {
+        let path = temp_file("corpus.txt", "python lolly crypt typo");
+        let setting = markov::Setting {
+            corpus: Some(path.clone()),
+            ..markov::Setting::default()
+        };
+        let char_set: CharSet = "asdfej ".chars().collect();
+        let mut words = Words::markov(setting, char_set.clone(), Language::English);
+        let line = words.line(40, &CharSet::new(), &[], &mut StdRng::seed_from_u64(1));
+        std::fs::remove_file(path).unwrap();
+
+        assert!(!line.trim().is_empty());
+        assert!(line.chars().all(|c| char_set.contains(&c)), "{:?}", line);
+    }
+
+    #[test]
+    fn letters_without_words_still_give_a_line() {
+        let char_set: CharSet = "kxzjvq ".chars().collect();
+        let mut words = Words::dictionary(char_set.clone(), Language::English);
+        let line = words.line(40, &CharSet::new(), &[], &mut StdRng::seed_from_u64(1));
+
+        assert!(!words.fits(&char_set));
+        assert!(!line.trim().is_empty());
+        assert!(line.chars().all(|c| char_set.contains(&c)), "{:?}", line);
+    }
+}
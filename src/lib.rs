@@ -1,6 +1,10 @@
 pub mod data;
+#[cfg(feature = "gui")]
 pub mod font;
+#[cfg(feature = "gui")]
 pub mod screen;
+#[cfg(feature = "gui")]
 pub mod style;
 
+#[cfg(feature = "gui")]
 pub type Element<'a, Message> = iced::Element<'a, Message, iced::Renderer<crate::style::Theme>>;
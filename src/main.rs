@@ -3,7 +3,7 @@ use iced::widget::Container;
 use iced::window;
 use iced::{self, Application, Command, Element, Length, Settings, Subscription};
 
-use linkage::data::{self, profile};
+use linkage::data::{self, keyboard, profile};
 use linkage::screen::{self, Screen};
 use linkage::{font, style};
 
@@ -24,6 +24,10 @@ struct Linkage {
     screen: Screen,
     theme: style::Theme,
     profiles: profile::List,
+    /// Layouts loaded from layout files
+    layouts: Vec<keyboard::Layout>,
+    /// Number of rolling backups to keep
+    backups: u8,
 }
 
 #[derive(Debug, Clone)]
@@ -46,6 +50,8 @@ impl Application for Linkage {
             screen: Screen::new(),
             theme: Default::default(),
             profiles: profile::List::default(),
+            layouts: Vec::new(),
+            backups: data::backup::DEFAULT_KEEP,
         };
         (
             linkage,
@@ -78,16 +84,59 @@ impl Application for Linkage {
                     screen,
                     theme,
                     profiles,
-                    ..
+                    layouts,
+                    backups,
                 } = self;
-                if let Some(event) = screen.update(profiles, message, theme.name()) {
+                if let Some(event) =
+                    screen.update(profiles, message, theme.name(), layouts, *backups)
+                {
                     match event {
                         screen::Event::ExitRequested => self.prepare_close(),
+                        screen::Event::ExportProfile(profile, path) => {
+                            Command::perform(screen::export_profile(profile, path), Message::Screen)
+                        }
+                        screen::Event::ExportStatistics(statistics, dir) => Command::perform(
+                            screen::export_statistics(statistics, dir),
+                            Message::Screen,
+                        ),
+                        screen::Event::ImportProfile(path) => {
+                            Command::perform(screen::import_profile(path), Message::Screen)
+                        }
+                        screen::Event::ListBackups => {
+                            Command::perform(screen::list_backups(), Message::Screen)
+                        }
+                        screen::Event::Loaded {
+                            theme: new_theme,
+                            layouts: loaded,
+                            backups: keep,
+                        } => {
+                            *theme = style::Theme::new(new_theme);
+                            *layouts = loaded;
+                            *backups = keep;
+                            self.save()
+                        }
+                        screen::Event::Reload => {
+                            Command::perform(screen::loading::load(), |message| {
+                                Message::Screen(screen::Message::Loading(message))
+                            })
+                        }
+                        screen::Event::Restore(backup) => {
+                            Command::perform(screen::restore(backup, *backups), Message::Screen)
+                        }
                         screen::Event::Save => self.save(),
                         screen::Event::SelectTheme(new_theme) => {
                             *theme = style::Theme::new(new_theme);
                             self.save()
                         }
+                        screen::Event::SetBackups(keep) => {
+                            *backups = keep;
+                            self.save()
+                        }
+                        screen::Event::StartFresh => {
+                            Command::perform(screen::loading::start_fresh(), |message| {
+                                Message::Screen(screen::Message::Loading(message))
+                            })
+                        }
                     }
                 } else {
                     Command::none()
@@ -98,7 +147,9 @@ impl Application for Linkage {
 
     fn subscription(&self) -> Subscription<Message> {
         Subscription::batch(vec![
-            self.screen.subscription().map(Message::Screen),
+            self.screen
+                .subscription(&self.profiles)
+                .map(Message::Screen),
             iced::subscription::events().map(Message::Event),
         ])
     }
@@ -164,7 +215,7 @@ impl Linkage {
     }
 
     fn save(&self) -> Command<Message> {
-        let saved = data::Saved::new(self.profiles.clone(), self.theme.name());
+        let saved = data::Saved::new(self.profiles.clone(), self.theme.name(), self.backups);
         Command::perform(save(saved), |_| Message::Saved)
     }
 }
@@ -1,12 +1,17 @@
-use crate::data::profile;
+use crate::data::backup::Backup;
+use crate::data::export::Statistics;
+use crate::data::keyboard::Layout;
+use crate::data::profile::{self, Profile};
 use crate::data::Theme;
 use crate::Element;
 
 use iced::Subscription;
+use std::path::PathBuf;
 
 pub mod loading;
 mod settings;
 pub mod training;
+mod typing_test;
 
 #[derive(Debug)]
 pub enum Screen {
@@ -16,6 +21,8 @@ pub enum Screen {
     Training(training::State),
     /// Changing user settings
     Settings(settings::State),
+    /// Timed typing test, apart from training
+    Test(typing_test::State),
     // /// Shutting down
     // Saving,
 }
@@ -26,12 +33,28 @@ pub enum Message {
     Loading(loading::Message),
     Settings(settings::Message),
     Training(training::Message),
+    Test(typing_test::Message),
 }
 
 pub enum Event {
     ExitRequested,
+    ExportProfile(Profile, PathBuf),
+    ExportStatistics(Statistics, PathBuf),
+    ImportProfile(PathBuf),
+    ListBackups,
+    Loaded {
+        theme: Theme,
+        layouts: Vec<Layout>,
+        backups: u8,
+    },
+    /// Load progress from disk again
+    Reload,
+    Restore(Backup),
     Save,
     SelectTheme(Theme),
+    SetBackups(u8),
+    /// Set aside the unreadable save file and load again
+    StartFresh,
 }
 
 impl Screen {
@@ -39,16 +62,20 @@ impl Screen {
         Self::Loading(loading::State::new())
     }
 
-    pub fn settings() -> Self {
-        Self::Settings(settings::State::new())
+    pub fn settings(layouts: &[Layout]) -> Self {
+        Self::Settings(settings::State::new(layouts))
     }
 
     pub fn training(profiles: &profile::List) -> Self {
-        Self::Training(training::State::new(&profiles.active().difficulty))
+        Self::Training(training::State::new(&profiles.active().targets()))
+    }
+
+    pub fn typing_test(profiles: &profile::List) -> Self {
+        Self::Test(typing_test::State::new(profiles))
     }
 
     pub fn go_back(&mut self, profiles: &profile::List) {
-        if let Screen::Settings(_) = self {
+        if let Screen::Settings(_) | Screen::Test(_) = self {
             *self = Screen::training(profiles);
         }
     }
@@ -58,19 +85,33 @@ impl Screen {
         profiles: &mut profile::List,
         message: Message,
         active: &'static str,
+        layouts: &[Layout],
+        backups: u8,
     ) -> Option<Event> {
         match self {
             Screen::Loading(state) => {
                 if let Message::Loading(message) = message {
                     if let Some(event) = state.update(message) {
                         match event {
+                            loading::Event::Exit => {
+                                return Some(Event::ExitRequested);
+                            }
                             loading::Event::Load {
                                 profiles: loaded,
                                 theme,
+                                layouts,
+                                backups,
                             } => {
                                 *profiles = loaded;
                                 *self = Screen::training(profiles);
-                                return Some(Event::SelectTheme(theme));
+                                return Some(Event::Loaded {
+                                    theme,
+                                    layouts,
+                                    backups,
+                                });
+                            }
+                            loading::Event::StartFresh => {
+                                return Some(Event::StartFresh);
                             }
                         }
                     }
@@ -84,7 +125,24 @@ impl Screen {
                                 return Some(Event::Save);
                             }
                             training::Event::Settings => {
-                                *self = Screen::settings();
+                                *self = Screen::settings(layouts);
+                            }
+                            training::Event::Test => {
+                                *self = Screen::typing_test(profiles);
+                            }
+                        }
+                    }
+                }
+            }
+            Screen::Test(state) => {
+                if let Message::Test(message) = message {
+                    if let Some(event) = state.update(profiles, message) {
+                        match event {
+                            typing_test::Event::Exit => {
+                                *self = Screen::training(profiles);
+                            }
+                            typing_test::Event::Save => {
+                                return Some(Event::Save);
                             }
                         }
                     }
@@ -92,17 +150,39 @@ impl Screen {
             }
             Screen::Settings(state) => {
                 if let Message::Settings(message) = message {
-                    if let Some(event) = state.update(profiles, message, active) {
+                    if let Some(event) = state.update(profiles, message, active, backups) {
                         match event {
                             settings::Event::Exit => {
                                 *self = Screen::training(profiles);
                             }
+                            settings::Event::ExportProfile(profile, path) => {
+                                return Some(Event::ExportProfile(profile, path));
+                            }
+                            settings::Event::ExportStatistics(statistics, dir) => {
+                                return Some(Event::ExportStatistics(statistics, dir));
+                            }
+                            settings::Event::ImportProfile(path) => {
+                                return Some(Event::ImportProfile(path));
+                            }
+                            settings::Event::ListBackups => {
+                                return Some(Event::ListBackups);
+                            }
+                            settings::Event::Reload => {
+                                *self = Screen::new();
+                                return Some(Event::Reload);
+                            }
+                            settings::Event::Restore(backup) => {
+                                return Some(Event::Restore(backup));
+                            }
                             settings::Event::Save => {
                                 return Some(Event::Save);
                             }
                             settings::Event::SelectTheme(theme) => {
                                 return Some(Event::SelectTheme(theme));
                             }
+                            settings::Event::SetBackups(keep) => {
+                                return Some(Event::SetBackups(keep));
+                            }
                         }
                     }
                 }
@@ -116,17 +196,45 @@ impl Screen {
             Screen::Loading(loading) => loading.view().map(Message::Loading),
             Screen::Settings(state) => state.view(profiles).map(Message::Settings),
             Screen::Training(state) => state.view(profiles).map(Message::Training),
+            Screen::Test(state) => state.view(profiles).map(Message::Test),
         }
     }
 
-    pub fn subscription(&self) -> Subscription<Message> {
+    pub fn subscription(&self, profiles: &profile::List) -> Subscription<Message> {
         match self {
-            Screen::Training { .. } => training::subscription().map(Message::Training),
+            Screen::Settings(state) => state.subscription().map(Message::Settings),
+            Screen::Training { .. } => training::subscription(profiles).map(Message::Training),
+            Screen::Test(state) => state.subscription().map(Message::Test),
             _ => Subscription::none(),
         }
     }
 }
 
+/// Export a profile from the settings screen
+pub async fn export_profile(profile: Profile, path: PathBuf) -> Message {
+    Message::Settings(settings::export_profile(profile, path).await)
+}
+
+/// Export statistics from the settings screen
+pub async fn export_statistics(statistics: Statistics, dir: PathBuf) -> Message {
+    Message::Settings(settings::export_statistics(statistics, dir).await)
+}
+
+/// Import a profile from the settings screen
+pub async fn import_profile(path: PathBuf) -> Message {
+    Message::Settings(settings::import_profile(path).await)
+}
+
+/// List backups for the settings screen
+pub async fn list_backups() -> Message {
+    Message::Settings(settings::list_backups().await)
+}
+
+/// Restore a backup from the settings screen
+pub async fn restore(backup: Backup, keep: u8) -> Message {
+    Message::Settings(settings::restore(backup, keep).await)
+}
+
 impl Default for Screen {
     fn default() -> Self {
         Screen::new()
@@ -1,52 +1,163 @@
 use crate::data;
+use crate::data::keyboard::{self, Layout};
 use crate::data::profile;
 use crate::data::Theme;
+use crate::style;
 use crate::Element;
 
-use iced::widget::text;
+use iced::widget::{text, Button, Column, Row, Text};
+use iced::Alignment;
+use std::path::PathBuf;
 
 #[derive(Debug, Default)]
-pub struct State {}
+pub struct State {
+    /// Why the save file could not be read
+    error: Option<String>,
+    /// Where the unreadable save file was kept after starting fresh, shown
+    /// before training begins
+    kept: Option<(PathBuf, Vec<Layout>)>,
+}
 
 #[derive(Debug, Clone)]
 pub enum Message {
-    Loaded(data::Saved),
+    ContinuePressed,
+    Loaded(Result<data::Saved, String>, Vec<Layout>),
+    QuitPressed,
+    StartFreshPressed,
+    /// The unreadable save file was moved to the given path
+    StartedFresh(Result<PathBuf, String>, Vec<Layout>),
 }
 
 pub enum Event {
+    Exit,
     Load {
         profiles: profile::List,
         theme: Theme,
+        layouts: Vec<Layout>,
+        backups: u8,
     },
+    StartFresh,
 }
 
 pub async fn load() -> Message {
-    let saved = data::Saved::load().await.unwrap_or_default();
-    Message::Loaded(saved)
+    let saved = data::Saved::load().await.map_err(|error| error.to_string());
+    let layouts = keyboard::load_custom().await;
+    Message::Loaded(saved, layouts)
+}
+
+/// Keep the unreadable save file under another name, then start over
+pub async fn start_fresh() -> Message {
+    let kept = data::Saved::set_aside()
+        .await
+        .map_err(|error| error.to_string());
+    let layouts = keyboard::load_custom().await;
+    Message::StartedFresh(kept, layouts)
 }
 
 impl State {
     pub fn new() -> Self {
-        Self {}
+        Self {
+            error: None,
+            kept: None,
+        }
     }
 
     pub fn view(&self) -> Element<Message> {
-        text("Loading").into()
+        if let Some((kept, _)) = &self.kept {
+            return Column::new()
+                .push(Text::new("Started fresh").size(18))
+                .push(
+                    Text::new(format!(
+                        "Your unreadable save file was kept at {}",
+                        kept.display()
+                    ))
+                    .size(14),
+                )
+                .push(
+                    Button::new(Text::new("Start Training").size(14))
+                        .on_press(Message::ContinuePressed)
+                        .style(style::Button::Accept)
+                        .padding(10),
+                )
+                .width(480)
+                .spacing(20)
+                .align_items(Alignment::Center)
+                .into();
+        }
+        let Some(error) = &self.error else {
+            return text("Loading").into();
+        };
+
+        let buttons = Row::new()
+            .push(
+                Button::new(Text::new("Quit").size(14))
+                    .on_press(Message::QuitPressed)
+                    .style(style::Button::Reject)
+                    .padding(10),
+            )
+            .push(
+                Button::new(Text::new("Start Fresh").size(14))
+                    .on_press(Message::StartFreshPressed)
+                    .style(style::Button::Accept)
+                    .padding(10),
+            )
+            .spacing(10);
+
+        Column::new()
+            .push(Text::new("Could not load your progress").size(18))
+            .push(Text::new(error).size(14).style(style::Text::Error))
+            .push(
+                Text::new(
+                    "Your save file has been left untouched. Quit to keep it as it is, or \
+                     start fresh to keep a copy named save-<time>.dat.unreadable in the \
+                     Linkage data folder and begin a new save.",
+                )
+                .size(14),
+            )
+            .push(buttons)
+            .width(480)
+            .spacing(20)
+            .align_items(Alignment::Center)
+            .into()
     }
 
     pub fn update(&mut self, message: Message) -> Option<Event> {
         match message {
-            Message::Loaded(saved) => {
-                let data::Saved {
-                    profiles,
-                    theme_name,
-                    ..
-                } = saved;
-                Some(Event::Load {
-                    profiles: profiles.into(),
-                    theme: Theme::from_name(&theme_name).unwrap_or_default(),
-                })
+            Message::ContinuePressed => {
+                let (_, layouts) = self.kept.take()?;
+                Some(load_event(data::Saved::default(), layouts))
+            }
+            Message::Loaded(Ok(saved), layouts) => {
+                self.error = None;
+                Some(load_event(saved, layouts))
+            }
+            Message::Loaded(Err(error), _) | Message::StartedFresh(Err(error), _) => {
+                self.error = Some(error);
+                None
+            }
+            Message::QuitPressed => Some(Event::Exit),
+            Message::StartFreshPressed => Some(Event::StartFresh),
+            Message::StartedFresh(Ok(kept), layouts) => {
+                self.error = None;
+                self.kept = Some((kept, layouts));
+                None
             }
         }
     }
 }
+
+/// Hand loaded data over to training
+fn load_event(saved: data::Saved, layouts: Vec<Layout>) -> Event {
+    let data::Saved {
+        profiles,
+        theme_name,
+        backups,
+        ..
+    } = saved;
+    Event::Load {
+        profiles: profiles.into(),
+        theme: Theme::from_name(&theme_name).unwrap_or_default(),
+        layouts,
+        backups,
+    }
+}
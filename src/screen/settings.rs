@@ -1,43 +1,103 @@
+use crate::data::keyboard::Layout;
 use crate::data::{self, Theme};
 use crate::style;
 use crate::Element;
 
 use iced::widget::{container, Button, Column, Container, Row, Rule, Scrollable, Text};
-use iced::Length;
+use iced::{Length, Subscription};
+use std::path::PathBuf;
 
+mod backup;
 mod profile;
+mod replay;
+mod statistics;
 mod theme;
 
 #[derive(Debug)]
 pub struct State {
     screen: Screen,
+    layouts: Vec<Layout>,
 }
 
 #[derive(Debug)]
 pub enum Screen {
+    Backup(backup::State),
     Profile(profile::State),
+    Replay(replay::State),
+    Statistics(statistics::State),
     Theme(theme::State),
 }
 
 #[derive(Debug, Clone)]
 pub enum Message {
     BackButtonPressed,
+    Backup(backup::Message),
+    BackupsPressed,
     Profile(profile::Message),
     ProfilesPressed,
+    Replay(replay::Message),
+    ReplaysPressed,
+    Statistics(statistics::Message),
+    StatisticsPressed,
     Theme(theme::Message),
     ThemesPressed,
 }
 
 pub enum Event {
     Exit,
+    ExportProfile(data::profile::Profile, PathBuf),
+    ExportStatistics(data::export::Statistics, PathBuf),
+    ImportProfile(PathBuf),
+    /// List backups for the backup screen
+    ListBackups,
+    /// Progress was restored from a backup and must be loaded again
+    Reload,
+    Restore(data::backup::Backup),
     Save,
     SelectTheme(Theme),
+    SetBackups(u8),
+}
+
+pub async fn list_backups() -> Message {
+    Message::Backup(backup::Message::Listed(data::backup::list().await))
+}
+
+pub async fn export_profile(profile: data::profile::Profile, path: PathBuf) -> Message {
+    let result = profile
+        .export(&path)
+        .await
+        .map(|_| path)
+        .map_err(|error| error.to_string());
+    Message::Profile(profile::Message::Exported(result))
+}
+
+pub async fn export_statistics(statistics: data::export::Statistics, dir: PathBuf) -> Message {
+    let result = statistics
+        .write(&dir)
+        .await
+        .map_err(|error| error.to_string());
+    Message::Statistics(statistics::Message::Exported(result))
+}
+
+pub async fn import_profile(path: PathBuf) -> Message {
+    let result = data::profile::Profile::import(&path)
+        .await
+        .map_err(|error| error.to_string());
+    Message::Profile(profile::Message::Imported(result))
+}
+
+pub async fn restore(backup: data::backup::Backup, keep: u8) -> Message {
+    let result = data::Saved::restore(&backup, keep)
+        .await
+        .map_err(|error| error.to_string());
+    Message::Backup(backup::Message::Restored(result))
 }
 
 impl State {
-    pub fn new() -> Self {
+    pub fn new(layouts: &[Layout]) -> Self {
         Self {
-            screen: Screen::profile(),
+            screen: Screen::profile(layouts),
+            layouts: layouts.to_vec(),
         }
     }
 
@@ -46,21 +106,72 @@ impl State {
         profiles: &mut data::profile::List,
         message: Message,
         active: &'static str,
+        backups: u8,
     ) -> Option<Event> {
-        let State { ref mut screen, .. } = self;
+        let State {
+            ref mut screen,
+            ref layouts,
+        } = self;
         match message {
             Message::BackButtonPressed => {
                 return Some(Event::Exit);
             }
+            Message::Backup(message) => {
+                if let Screen::Backup(state) = screen {
+                    if let Some(event) = state.update(message) {
+                        return Some(match event {
+                            backup::Event::Restore(backup) => Event::Restore(backup),
+                            backup::Event::Restored => Event::Reload,
+                            backup::Event::SetKeep(keep) => Event::SetBackups(keep),
+                        });
+                    }
+                }
+            }
+            Message::BackupsPressed => {
+                *screen = Screen::backup(backups);
+                return Some(Event::ListBackups);
+            }
             Message::Profile(message) => {
                 if let Screen::Profile(state) = screen {
-                    if state.update(profiles, message) {
-                        return Some(Event::Save);
+                    if let Some(event) = state.update(profiles, message) {
+                        return Some(match event {
+                            profile::Event::Export(profile, path) => {
+                                Event::ExportProfile(profile, path)
+                            }
+                            profile::Event::Import(path) => Event::ImportProfile(path),
+                            profile::Event::Save => Event::Save,
+                        });
                     }
                 }
             }
             Message::ProfilesPressed => {
-                *screen = Screen::profile();
+                *screen = Screen::profile(layouts);
+            }
+            Message::Replay(message) => {
+                if let Screen::Replay(state) = screen {
+                    if let Some(event) = state.update(profiles, message) {
+                        return Some(match event {
+                            replay::Event::Save => Event::Save,
+                        });
+                    }
+                }
+            }
+            Message::ReplaysPressed => {
+                *screen = Screen::replay();
+            }
+            Message::Statistics(message) => {
+                if let Screen::Statistics(state) = screen {
+                    if let Some(event) = state.update(profiles, message) {
+                        return Some(match event {
+                            statistics::Event::Export(statistics, dir) => {
+                                Event::ExportStatistics(statistics, dir)
+                            }
+                        });
+                    }
+                }
+            }
+            Message::StatisticsPressed => {
+                *screen = Screen::statistics();
             }
             Message::Theme(message) => {
                 if let Screen::Theme(state) = screen {
@@ -80,8 +191,15 @@ impl State {
         None
     }
 
+    pub fn subscription(&self) -> Subscription<Message> {
+        match &self.screen {
+            Screen::Replay(state) => state.subscription().map(Message::Replay),
+            _ => Subscription::none(),
+        }
+    }
+
     pub fn view(&self, profiles: &data::profile::List) -> Element<Message> {
-        let State { screen } = self;
+        let State { screen, .. } = self;
 
         let back_button = Button::new(Text::new("\u{2190} Back").size(14))
             .on_press(Message::BackButtonPressed)
@@ -94,11 +212,26 @@ impl State {
                 message: Message::ProfilesPressed,
                 is_active: matches!(screen, Screen::Profile(..)),
             },
+            MenuItem {
+                label: "Statistics",
+                message: Message::StatisticsPressed,
+                is_active: matches!(screen, Screen::Statistics(_)),
+            },
+            MenuItem {
+                label: "Replays",
+                message: Message::ReplaysPressed,
+                is_active: matches!(screen, Screen::Replay(_)),
+            },
             MenuItem {
                 label: "Themes",
                 message: Message::ThemesPressed,
                 is_active: matches!(screen, Screen::Theme(_)),
             },
+            MenuItem {
+                label: "Backups",
+                message: Message::BackupsPressed,
+                is_active: matches!(screen, Screen::Backup(_)),
+            },
         ];
 
         let menu = Column::with_children(
@@ -149,12 +282,6 @@ impl State {
     }
 }
 
-impl Default for State {
-    fn default() -> Self {
-        State::new()
-    }
-}
-
 struct MenuItem {
     pub label: &'static str,
     pub message: Message,
@@ -162,8 +289,20 @@ struct MenuItem {
 }
 
 impl Screen {
-    fn profile() -> Self {
-        Screen::Profile(profile::State::new())
+    fn backup(keep: u8) -> Self {
+        Screen::Backup(backup::State::new(keep))
+    }
+
+    fn profile(layouts: &[Layout]) -> Self {
+        Screen::Profile(profile::State::new(layouts))
+    }
+
+    fn replay() -> Self {
+        Screen::Replay(replay::State::new())
+    }
+
+    fn statistics() -> Self {
+        Screen::Statistics(statistics::State::new())
     }
 
     fn theme(active: &'static str) -> Self {
@@ -172,7 +311,10 @@ impl Screen {
 
     fn view(&self, profiles: &data::profile::List) -> Element<Message> {
         match self {
+            Screen::Backup(state) => state.view().map(Message::Backup),
             Screen::Profile(state) => state.view(profiles).map(Message::Profile),
+            Screen::Replay(state) => state.view(profiles).map(Message::Replay),
+            Screen::Statistics(state) => state.view(profiles).map(Message::Statistics),
             Screen::Theme(state) => state.view().map(Message::Theme),
         }
     }
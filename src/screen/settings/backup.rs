@@ -0,0 +1,161 @@
+use crate::data::backup::{self, Backup};
+use crate::font;
+use crate::style;
+use crate::Element;
+
+use iced::widget::{Button, Column, Container, PickList, Row, Scrollable, Text};
+use iced::{Alignment, Length};
+
+#[derive(Debug)]
+pub struct State {
+    /// Number of rolling backups to keep
+    keep: u8,
+    /// Backups on disk, newest first, once they have been listed
+    backups: Option<Vec<Backup>>,
+    /// Backup waiting for confirmation before it replaces the current save
+    confirming: Option<usize>,
+    /// Why the last restore failed
+    error: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    KeepChanged(u8),
+    Listed(Vec<Backup>),
+    RestoreAccept,
+    RestoreCancel,
+    RestorePressed(usize),
+    Restored(Result<(), String>),
+}
+
+pub enum Event {
+    Restore(Backup),
+    Restored,
+    SetKeep(u8),
+}
+
+impl State {
+    pub fn new(keep: u8) -> Self {
+        Self {
+            keep,
+            backups: None,
+            confirming: None,
+            error: None,
+        }
+    }
+
+    pub fn update(&mut self, message: Message) -> Option<Event> {
+        match message {
+            Message::KeepChanged(keep) => {
+                self.keep = keep;
+                return Some(Event::SetKeep(keep));
+            }
+            Message::Listed(backups) => {
+                self.backups = Some(backups);
+            }
+            Message::RestoreAccept => {
+                let backup = self
+                    .confirming
+                    .take()
+                    .and_then(|index| self.backups.as_ref()?.get(index).cloned());
+                if let Some(backup) = backup {
+                    self.error = None;
+                    return Some(Event::Restore(backup));
+                }
+            }
+            Message::RestoreCancel => {
+                self.confirming = None;
+            }
+            Message::RestorePressed(index) => {
+                self.confirming = Some(index);
+            }
+            Message::Restored(Ok(())) => {
+                return Some(Event::Restored);
+            }
+            Message::Restored(Err(error)) => {
+                self.error = Some(error);
+            }
+        }
+        None
+    }
+
+    pub fn view(&self) -> Element<Message> {
+        let title = Container::new(Text::new("Backups").size(18)).padding(6);
+
+        let keep_title = Text::new("Backups to keep").size(14).font(font::Font::Thin);
+        let keep_pick_list = PickList::new(
+            (0..=backup::MAX_KEEP).collect::<Vec<u8>>(),
+            Some(self.keep),
+            Message::KeepChanged,
+        )
+        .text_size(15);
+        let keep_section = Column::new()
+            .padding([0, 0, 0, 6])
+            .spacing(5)
+            .push(keep_title)
+            .push(keep_pick_list)
+            .push(Text::new("Your progress is backed up at most once an hour").size(14));
+
+        let restore_title = Text::new("Restore").size(14).font(font::Font::Thin);
+        let mut restore_section = Column::new()
+            .padding([0, 0, 0, 6])
+            .spacing(5)
+            .push(restore_title);
+
+        match &self.backups {
+            None => {
+                restore_section = restore_section.push(Text::new("Loading").size(14));
+            }
+            Some(backups) if backups.is_empty() => {
+                restore_section = restore_section.push(Text::new("No backups yet").size(14));
+            }
+            Some(backups) => {
+                for (i, backup) in backups.iter().enumerate() {
+                    let mut row = Row::new()
+                        .push(Text::new(backup.to_string()).size(14).width(Length::Fill))
+                        .align_items(Alignment::Center)
+                        .spacing(5);
+
+                    row = if self.confirming == Some(i) {
+                        row.push(Text::new("Replace current progress?").size(14))
+                            .push(
+                                Button::new(Text::new("\u{2714}").size(14))
+                                    .on_press(Message::RestoreAccept)
+                                    .style(style::Button::Accept),
+                            )
+                            .push(
+                                Button::new(Text::new("\u{2716}").size(14))
+                                    .on_press(Message::RestoreCancel)
+                                    .style(style::Button::Reject),
+                            )
+                    } else {
+                        row.push(
+                            Button::new(Text::new("Restore").size(14))
+                                .on_press(Message::RestorePressed(i))
+                                .style(style::Button::Basic),
+                        )
+                    };
+                    restore_section = restore_section.push(row);
+                }
+                restore_section = restore_section.push(
+                    Text::new("Your current progress is backed up before restoring").size(14),
+                );
+            }
+        }
+
+        if let Some(error) = &self.error {
+            restore_section =
+                restore_section.push(Text::new(error).size(14).style(style::Text::Error));
+        }
+
+        let content = Column::new()
+            .push(title)
+            .push(keep_section)
+            .push(restore_section)
+            .width(Length::Fill)
+            .spacing(20)
+            .padding(10);
+
+        Scrollable::new(content).height(Length::Fill).into()
+    }
+}
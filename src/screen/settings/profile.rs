@@ -1,6 +1,12 @@
 use crate::data::keyboard::{self, Layout};
+use crate::data::language::{self, Language};
+use crate::data::markov::{self, WordLengths, ORDERS, WORD_LENGTHS};
 use crate::data::profile;
-use crate::data::training::Difficulty;
+use crate::data::recording::{Ghost, GhostKind, GHOST_KINDS};
+use crate::data::training::{
+    Difficulty, Targets, RAISE_WPM, SEEDED_LINES, TARGET_ACCURACY_RANGE, TARGET_WPM_RANGE,
+};
+use crate::data::words;
 use crate::font;
 use crate::style;
 use crate::Element;
@@ -8,12 +14,19 @@ use crate::Element;
 use iced::widget::{
     container, Button, Column, Container, PickList, Row, Rule, Scrollable, Text, TextInput,
 };
-use iced::Length;
+use iced::{Alignment, Length};
+use itertools::Itertools;
+use std::path::PathBuf;
+
+/// Letter buttons in each row of the letters section
+const LETTERS_PER_ROW: usize = 20;
 
 #[derive(Debug)]
 pub struct State {
     menu: Menu,
     screen: Screen,
+    /// Built-in layouts followed by any loaded from layout files
+    layouts: Vec<Layout>,
 }
 
 #[derive(Debug)]
@@ -23,15 +36,42 @@ pub struct Menu {}
 pub enum Screen {
     Create {
         difficulty: Option<Difficulty>,
+        language: Option<Language>,
         layout: Option<Layout>,
+        layouts: Vec<Layout>,
         name_parsed: Option<profile::Name>,
         name_value: String,
     },
+    Import {
+        path_value: String,
+        /// Why the last import failed
+        error: Option<String>,
+    },
     Rename {
         name_parsed: Option<profile::Name>,
         name_value: String,
     },
-    View,
+    View {
+        /// Picked a word source read from disk, but no path accepted yet
+        choosing: Option<words::Source>,
+        words_path: String,
+        /// Nothing could be found at the last path accepted
+        words_error: bool,
+        export_path: String,
+        /// Where the profile was last exported to, or why that failed
+        exported: Option<Result<PathBuf, String>>,
+        seed_value: String,
+        /// The seed entered is not a number
+        seed_error: bool,
+        target_wpm: String,
+        target_accuracy: String,
+        /// A custom target entered is not a number
+        targets_error: bool,
+        /// Letters to unlock next, as typed
+        order_value: String,
+        /// Why the last letter pressed could not be taken out of training
+        lock_error: Option<String>,
+    },
 }
 
 #[derive(Debug, Clone)]
@@ -39,39 +79,87 @@ pub enum Message {
     CreateAccept,
     CreateCancel,
     DifficultyChanged(Difficulty),
+    ExportAccept,
+    ExportPathInput(String),
+    Exported(Result<PathBuf, String>),
+    GhostChanged(GhostKind),
+    ImportAccept,
+    ImportCancel,
+    ImportPathInput(String),
+    ImportPressed,
+    Imported(Result<profile::Profile, String>),
+    LanguageChanged(Language),
     LayoutChanged(Layout),
+    LetterLocked(char),
+    LetterUnlocked(char),
+    MarkovCorpusCleared,
+    MarkovLengthsChanged(WordLengths),
+    MarkovOrderChanged(u8),
     NameInput(String),
     NewProfilePressed,
+    OrderAccept,
+    OrderInput(String),
     ProfilePressed(usize),
     RenameAccept,
     RenameCancel,
     RenamePressed,
+    SeedClear,
+    SeedInput(String),
+    SeedKeep,
+    SeedReplay,
+    SourceChanged(words::Source),
+    TargetAccuracyInput(String),
+    TargetsAccept,
+    TargetsAutoRaise(bool),
+    TargetWpmInput(String),
+    UnlockAllPressed,
+    WordsPathAccept,
+    WordsPathInput(String),
+}
+
+pub enum Event {
+    Export(profile::Profile, PathBuf),
+    Import(PathBuf),
+    Save,
 }
 
 impl State {
-    pub fn new() -> Self {
+    pub fn new(custom_layouts: &[Layout]) -> Self {
+        let layouts = keyboard::ALL
+            .iter()
+            .chain(custom_layouts.iter())
+            .cloned()
+            .collect();
+
         Self {
             menu: Menu::new(),
             screen: Screen::viewing(),
+            layouts,
         }
     }
 
-    pub fn update(&mut self, profiles: &mut profile::List, message: Message) -> bool {
+    pub fn update(&mut self, profiles: &mut profile::List, message: Message) -> Option<Event> {
         match message {
             Message::CreateAccept => {
                 if let Screen::Create {
                     difficulty: Some(difficulty),
+                    language: Some(language),
                     layout: Some(layout),
                     name_parsed: Some(name_parsed),
                     ..
                 } = &self.screen
                 {
-                    let profile = profile::Profile::new(name_parsed.clone(), *layout, *difficulty);
+                    let profile = profile::Profile::new(
+                        name_parsed.clone(),
+                        layout.clone(),
+                        *language,
+                        *difficulty,
+                    );
                     profiles.insert_active(profile);
 
                     self.screen = Screen::viewing();
 
-                    return true;
+                    return Some(Event::Save);
                 }
             }
             Message::CreateCancel => {
@@ -86,7 +174,99 @@ impl State {
                 Screen::View { .. } => {
                     profiles.active_mut().difficulty = new_difficulty;
 
-                    return true;
+                    return Some(Event::Save);
+                }
+                _ => {}
+            },
+            Message::ExportAccept => {
+                if let Screen::View {
+                    ref export_path, ..
+                } = self.screen
+                {
+                    let profile = profiles.export_active();
+                    let mut path = PathBuf::from(export_path.trim());
+                    if path.is_dir() {
+                        path.push(format!("{}.json", profile.name().file_name()));
+                    }
+
+                    return Some(Event::Export(profile, path));
+                }
+            }
+            Message::ExportPathInput(path) => {
+                if let Screen::View {
+                    ref mut export_path,
+                    ref mut exported,
+                    ..
+                } = self.screen
+                {
+                    *export_path = path;
+                    *exported = None;
+                }
+            }
+            Message::Exported(result) => {
+                if let Screen::View {
+                    ref mut exported, ..
+                } = self.screen
+                {
+                    *exported = Some(result);
+                }
+            }
+            Message::GhostChanged(kind) => {
+                let ghost = match kind {
+                    GhostKind::Off => Ghost::Off,
+                    GhostKind::Best => Ghost::Best,
+                    // A line is chosen from the replays
+                    GhostKind::Line => return None,
+                };
+                profiles.active_mut().ghost = ghost;
+
+                return Some(Event::Save);
+            }
+            Message::ImportAccept => {
+                if let Screen::Import { path_value, .. } = &self.screen {
+                    return Some(Event::Import(PathBuf::from(path_value.trim())));
+                }
+            }
+            Message::ImportCancel => {
+                self.screen = Screen::viewing();
+            }
+            Message::ImportPathInput(path) => {
+                if let Screen::Import {
+                    ref mut path_value,
+                    ref mut error,
+                } = self.screen
+                {
+                    *path_value = path;
+                    *error = None;
+                }
+            }
+            Message::ImportPressed => {
+                self.screen = Screen::Import {
+                    path_value: String::new(),
+                    error: None,
+                };
+            }
+            Message::Imported(Ok(profile)) => {
+                profiles.import(profile);
+                self.screen = Screen::viewing();
+
+                return Some(Event::Save);
+            }
+            Message::Imported(Err(message)) => {
+                if let Screen::Import { ref mut error, .. } = self.screen {
+                    *error = Some(message);
+                }
+            }
+            Message::LanguageChanged(new_language) => match self.screen {
+                Screen::Create {
+                    ref mut language, ..
+                } => {
+                    *language = Some(new_language);
+                }
+                Screen::View { .. } => {
+                    profiles.active_mut().set_language(new_language);
+
+                    return Some(Event::Save);
                 }
                 _ => {}
             },
@@ -95,6 +275,40 @@ impl State {
                     *layout = Some(new_layout);
                 }
             }
+            Message::LetterLocked(letter) => {
+                let result = profiles.active_mut().lock(letter);
+                if let Screen::View {
+                    ref mut lock_error, ..
+                } = self.screen
+                {
+                    *lock_error = result
+                        .err()
+                        .map(|error| format!("Could not take out {}: {}", letter, error));
+                }
+                if result.is_ok() {
+                    return Some(Event::Save);
+                }
+            }
+            Message::LetterUnlocked(letter) => {
+                if let Screen::View {
+                    ref mut lock_error, ..
+                } = self.screen
+                {
+                    *lock_error = None;
+                }
+                profiles.active_mut().unlock(letter);
+
+                return Some(Event::Save);
+            }
+            Message::MarkovCorpusCleared => {
+                return change_markov(profiles, |setting| setting.corpus = None);
+            }
+            Message::MarkovLengthsChanged(lengths) => {
+                return change_markov(profiles, |setting| setting.lengths = lengths);
+            }
+            Message::MarkovOrderChanged(order) => {
+                return change_markov(profiles, |setting| setting.order = order);
+            }
             Message::NameInput(new_name) => match &mut self.screen {
                 Screen::Create {
                     ref mut name_parsed,
@@ -117,12 +331,34 @@ impl State {
                 _ => {}
             },
             Message::NewProfilePressed => {
-                self.screen = Screen::creating();
+                self.screen = Screen::creating(&self.layouts);
+            }
+            Message::OrderAccept => {
+                if let Screen::View {
+                    ref mut order_value,
+                    ..
+                } = self.screen
+                {
+                    let first: Vec<char> = order_value.chars().filter(|c| *c != ' ').collect();
+                    profiles.active_mut().reorder(&first);
+                    order_value.clear();
+
+                    return Some(Event::Save);
+                }
+            }
+            Message::OrderInput(value) => {
+                if let Screen::View {
+                    ref mut order_value,
+                    ..
+                } = self.screen
+                {
+                    *order_value = value;
+                }
             }
             Message::ProfilePressed(index) => {
                 profiles.select(index);
                 self.screen = Screen::viewing();
-                return true;
+                return Some(Event::Save);
             }
             Message::RenameAccept => {
                 if let Screen::Rename {
@@ -133,7 +369,7 @@ impl State {
                     profiles.active_mut().name = name_parsed.clone();
                     self.screen = Screen::viewing();
 
-                    return true;
+                    return Some(Event::Save);
                 }
             }
             Message::RenameCancel => {
@@ -142,12 +378,178 @@ impl State {
             Message::RenamePressed => {
                 self.screen = Screen::renaming(profiles.active().name.to_string());
             }
+            Message::SeedClear => {
+                profiles.active_mut().set_seed(None);
+
+                return Some(Event::Save);
+            }
+            Message::SeedInput(value) => {
+                if let Screen::View {
+                    ref mut seed_value,
+                    ref mut seed_error,
+                    ..
+                } = self.screen
+                {
+                    *seed_value = value;
+                    *seed_error = false;
+                }
+            }
+            Message::SeedKeep | Message::SeedReplay => {
+                if let Screen::View {
+                    ref seed_value,
+                    ref mut seed_error,
+                    ..
+                } = self.screen
+                {
+                    let Ok(seed) = seed_value.trim().parse() else {
+                        *seed_error = true;
+                        return None;
+                    };
+                    if matches!(message, Message::SeedKeep) {
+                        profiles.active_mut().set_seed(Some(seed));
+
+                        return Some(Event::Save);
+                    }
+                    profiles.active_mut().replay(seed);
+                }
+            }
+            Message::SourceChanged(source) => {
+                if let Screen::View {
+                    ref mut choosing, ..
+                } = self.screen
+                {
+                    let setting = match source {
+                        words::Source::Dictionary => Some(words::Setting::Dictionary),
+                        words::Source::Random => Some(words::Setting::Random),
+                        words::Source::Markov => {
+                            Some(words::Setting::Markov(markov::Setting::default()))
+                        }
+                        words::Source::File | words::Source::Code => None,
+                    };
+                    *choosing = setting.is_none().then_some(source);
+                    if let Some(setting) = setting {
+                        profiles.active_mut().set_words(setting);
+
+                        return Some(Event::Save);
+                    }
+                }
+            }
+            Message::TargetAccuracyInput(value) => {
+                if let Screen::View {
+                    ref mut target_accuracy,
+                    ref mut targets_error,
+                    ..
+                } = self.screen
+                {
+                    *target_accuracy = value;
+                    *targets_error = false;
+                }
+            }
+            Message::TargetsAccept => {
+                if let Screen::View {
+                    ref mut target_wpm,
+                    ref mut target_accuracy,
+                    ref mut targets_error,
+                    ..
+                } = self.screen
+                {
+                    let active = profiles.active_mut();
+                    // A field left empty keeps its current target
+                    let wpm = match target_wpm.trim() {
+                        "" => Ok(f64::from(active.custom.wpm)),
+                        value => value.parse::<f64>(),
+                    };
+                    let accuracy = match target_accuracy.trim().trim_end_matches('%') {
+                        "" => Ok(100.0 * active.custom.accuracy),
+                        value => value.trim().parse::<f32>(),
+                    };
+                    let (Ok(wpm), Ok(accuracy)) = (wpm, accuracy) else {
+                        *targets_error = true;
+                        return None;
+                    };
+                    active.custom = Targets::new(wpm, accuracy / 100.0, active.custom.auto_raise);
+                    target_wpm.clear();
+                    target_accuracy.clear();
+
+                    return Some(Event::Save);
+                }
+            }
+            Message::TargetsAutoRaise(auto_raise) => {
+                profiles.active_mut().custom.auto_raise = auto_raise;
+
+                return Some(Event::Save);
+            }
+            Message::TargetWpmInput(value) => {
+                if let Screen::View {
+                    ref mut target_wpm,
+                    ref mut targets_error,
+                    ..
+                } = self.screen
+                {
+                    *target_wpm = value;
+                    *targets_error = false;
+                }
+            }
+            Message::UnlockAllPressed => {
+                profiles.active_mut().unlock_all();
+
+                return Some(Event::Save);
+            }
+            Message::WordsPathAccept => {
+                if let Screen::View {
+                    ref mut choosing,
+                    ref words_path,
+                    ref mut words_error,
+                    ..
+                } = self.screen
+                {
+                    let path = PathBuf::from(words_path.trim());
+                    let source = choosing
+                        .unwrap_or_else(|| profiles.active().session.words_setting().source());
+                    // Code may be read from a whole directory
+                    let setting = match (source, profiles.active().session.words_setting()) {
+                        (words::Source::File, _) if path.is_file() => {
+                            Some(words::Setting::File(path))
+                        }
+                        (words::Source::Code, _) if path.exists() => {
+                            Some(words::Setting::Code(path))
+                        }
+                        (words::Source::Markov, words::Setting::Markov(setting))
+                            if path.is_file() =>
+                        {
+                            Some(words::Setting::Markov(markov::Setting {
+                                corpus: Some(path),
+                                ..setting
+                            }))
+                        }
+                        _ => None,
+                    };
+                    if let Some(setting) = setting {
+                        profiles.active_mut().set_words(setting);
+                        *choosing = None;
+
+                        return Some(Event::Save);
+                    }
+                    *words_error = true;
+                }
+            }
+            Message::WordsPathInput(path) => {
+                if let Screen::View {
+                    ref mut words_path,
+                    ref mut words_error,
+                    ..
+                } = self.screen
+                {
+                    *words_path = path;
+                    *words_error = false;
+                }
+            }
         }
-        false
+        None
     }
 
     pub fn view(&self, profiles: &profile::List) -> Element<Message> {
-        let State { menu, screen } = self;
+        let State { menu, screen, .. } = self;
 
         let menu = menu.view(profiles);
 
@@ -208,18 +610,31 @@ impl Menu {
             .on_press(Message::NewProfilePressed)
             .width(Length::Fill);
 
-        Scrollable::new(Column::new().push(menu).push(new_button).width(175))
-            .height(Length::Fill)
-            .into()
+        let import_button = Button::new(Text::new("\u{2193} Import Profile").size(14))
+            .style(style::Button::Menu { selected: false })
+            .on_press(Message::ImportPressed)
+            .width(Length::Fill);
+
+        Scrollable::new(
+            Column::new()
+                .push(menu)
+                .push(new_button)
+                .push(import_button)
+                .width(175),
+        )
+        .height(Length::Fill)
+        .into()
     }
 }
 
 impl Screen {
-    fn creating() -> Self {
+    fn creating(layouts: &[Layout]) -> Self {
         Self::Create {
             difficulty: Some(Difficulty::default()),
+            language: Some(Language::default()),
 
             layout: None,
+            layouts: layouts.to_vec(),
 
             name_parsed: None,
             name_value: String::new(),
@@ -234,7 +649,20 @@ impl Screen {
     }
 
     fn viewing() -> Self {
-        Self::View
+        Self::View {
+            choosing: None,
+            words_path: String::new(),
+            words_error: false,
+            export_path: String::new(),
+            exported: None,
+            seed_value: String::new(),
+            seed_error: false,
+            target_wpm: String::new(),
+            target_accuracy: String::new(),
+            targets_error: false,
+            order_value: String::new(),
+            lock_error: None,
+        }
     }
 
     fn view(&self, profiles: &profile::List) -> Element<Message> {
@@ -243,7 +671,9 @@ impl Screen {
         match self {
             Screen::Create {
                 difficulty,
+                language,
                 layout,
+                layouts,
                 name_parsed,
                 name_value,
             } => {
@@ -254,12 +684,21 @@ impl Screen {
 
                 let layout_title = Text::new("Keyboard Layout").size(14).font(font::Font::Thin);
                 let layout_pick_list =
-                    PickList::new(keyboard::ALL, *layout, Message::LayoutChanged).text_size(15);
+                    PickList::new(layouts.as_slice(), layout.clone(), Message::LayoutChanged)
+                        .text_size(15);
                 let layout_section = Column::new()
                     .spacing(5)
                     .push(layout_title)
                     .push(layout_pick_list);
 
+                let language_title = Text::new("Language").size(14).font(font::Font::Thin);
+                let language_pick_list =
+                    PickList::new(language::ALL, *language, Message::LanguageChanged).text_size(15);
+                let language_section = Column::new()
+                    .spacing(5)
+                    .push(language_title)
+                    .push(language_pick_list);
+
                 let difficulty_title = Text::new("Difficulty").size(14).font(font::Font::Thin);
                 let difficulty_pick_list =
                     PickList::new(Difficulty::ALL, *difficulty, Message::DifficultyChanged)
@@ -284,9 +723,44 @@ impl Screen {
                 content = content
                     .push(name_input)
                     .push(layout_section)
+                    .push(language_section)
                     .push(difficulty_section)
                     .push(button_row);
             }
+            Screen::Import { path_value, error } => {
+                let title = Text::new("Import Profile").size(18);
+
+                let mut path_input = TextInput::new(
+                    "Path to an exported profile",
+                    path_value,
+                    Message::ImportPathInput,
+                )
+                .width(Length::Fill)
+                .padding(6)
+                .size(15);
+
+                let mut accept =
+                    Button::new(centered_text("\u{2714}", 24, 20)).style(style::Button::Accept);
+                if !path_value.trim().is_empty() {
+                    path_input = path_input.on_submit(Message::ImportAccept);
+                    accept = accept.on_press(Message::ImportAccept);
+                }
+
+                let cancel = Button::new(centered_text("\u{2716}", 24, 20))
+                    .style(style::Button::Reject)
+                    .on_press(Message::ImportCancel);
+
+                let path_row = Row::new()
+                    .push(path_input)
+                    .push(accept)
+                    .push(cancel)
+                    .spacing(5);
+
+                content = content.push(title).push(path_row);
+                if let Some(error) = error {
+                    content = content.push(Text::new(error).size(14).style(style::Text::Error));
+                }
+            }
             Screen::Rename {
                 name_parsed,
                 name_value,
@@ -316,7 +790,20 @@ impl Screen {
 
                 content = content.push(name_row);
             }
-            Screen::View => {
+            Screen::View {
+                choosing,
+                words_path,
+                words_error,
+                export_path,
+                exported,
+                seed_value,
+                seed_error,
+                target_wpm,
+                target_accuracy,
+                targets_error,
+                order_value,
+                lock_error,
+            } => {
                 let rename_button =
                     Button::new(Text::new(profiles.active().name.to_string()).size(18))
                         .style(style::Button::Text)
@@ -331,6 +818,19 @@ impl Screen {
                     .push(layout_title)
                     .push(layout_name);
 
+                let language_title = Text::new("Language").size(14).font(font::Font::Thin);
+                let language_pick_list = PickList::new(
+                    language::ALL,
+                    Some(profiles.active().language),
+                    Message::LanguageChanged,
+                )
+                .text_size(15);
+                let language_section = Column::new()
+                    .padding([0, 0, 0, 6])
+                    .spacing(5)
+                    .push(language_title)
+                    .push(language_pick_list);
+
                 let difficulty_title = Text::new("Difficulty").size(14).font(font::Font::Thin);
                 let difficulty_pick_list = PickList::new(
                     Difficulty::ALL,
@@ -338,16 +838,317 @@ impl Screen {
                     Message::DifficultyChanged,
                 )
                 .text_size(15);
-                let difficulty_section = Column::new()
+                let mut difficulty_section = Column::new()
                     .padding([0, 0, 0, 6])
                     .spacing(5)
                     .push(difficulty_title)
                     .push(difficulty_pick_list);
 
+                if profiles.active().difficulty == Difficulty::Custom {
+                    let custom = profiles.active().custom;
+                    let mut wpm_input = TextInput::new(
+                        &format!("{} wpm", f64::from(custom.wpm)),
+                        target_wpm,
+                        Message::TargetWpmInput,
+                    )
+                    .width(Length::Fill)
+                    .padding(6)
+                    .size(15);
+                    let mut accuracy_input = TextInput::new(
+                        &format!("{:.0}% clean", 100.0 * custom.accuracy),
+                        target_accuracy,
+                        Message::TargetAccuracyInput,
+                    )
+                    .width(Length::Fill)
+                    .padding(6)
+                    .size(15);
+                    let mut accept =
+                        Button::new(centered_text("\u{2714}", 24, 20)).style(style::Button::Accept);
+                    if !target_wpm.trim().is_empty() || !target_accuracy.trim().is_empty() {
+                        wpm_input = wpm_input.on_submit(Message::TargetsAccept);
+                        accuracy_input = accuracy_input.on_submit(Message::TargetsAccept);
+                        accept = accept.on_press(Message::TargetsAccept);
+                    }
+
+                    let raise_button = if custom.auto_raise {
+                        Button::new(Text::new("Stop Raising").size(14))
+                            .on_press(Message::TargetsAutoRaise(false))
+                    } else {
+                        Button::new(Text::new("Raise Automatically").size(14))
+                            .on_press(Message::TargetsAutoRaise(true))
+                    }
+                    .style(style::Button::Basic)
+                    .padding(6);
+                    let raise_note = if custom.auto_raise {
+                        format!(
+                            "The speed target goes up by {} wpm whenever every letter is \
+                             unlocked and has reached it",
+                            RAISE_WPM
+                        )
+                    } else {
+                        "The targets stay as they are once every letter is unlocked".to_string()
+                    };
+
+                    difficulty_section = difficulty_section
+                        .push(
+                            Row::new()
+                                .push(wpm_input)
+                                .push(accuracy_input)
+                                .push(accept)
+                                .spacing(5),
+                        )
+                        .push(
+                            Row::new()
+                                .push(Text::new(raise_note).size(14).width(Length::Fill))
+                                .push(raise_button)
+                                .spacing(5),
+                        );
+                    if *targets_error {
+                        difficulty_section = difficulty_section.push(
+                            Text::new(format!(
+                                "Targets are numbers. Speed is kept between {} and {} wpm, \
+                                 accuracy between {:.0}% and {:.0}%.",
+                                TARGET_WPM_RANGE.start(),
+                                TARGET_WPM_RANGE.end(),
+                                100.0 * TARGET_ACCURACY_RANGE.start(),
+                                100.0 * TARGET_ACCURACY_RANGE.end()
+                            ))
+                            .size(14)
+                            .style(style::Text::Error),
+                        );
+                    }
+                }
+
+                let letters_section =
+                    letters_section(profiles.active(), order_value, lock_error.as_deref());
+
+                let setting = profiles.active().session.words_setting();
+                let source = choosing.unwrap_or_else(|| setting.source());
+                let words_title = Text::new("Words").size(14).font(font::Font::Thin);
+                let words_pick_list =
+                    PickList::new(words::SOURCES, Some(source), Message::SourceChanged)
+                        .text_size(15);
+                let mut words_section = Column::new()
+                    .padding([0, 0, 0, 6])
+                    .spacing(5)
+                    .push(words_title)
+                    .push(words_pick_list);
+
+                if let (words::Setting::Markov(pseudo), words::Source::Markov) = (&setting, source)
+                {
+                    let order_pick_list =
+                        PickList::new(ORDERS, Some(pseudo.order), Message::MarkovOrderChanged)
+                            .text_size(15);
+                    let lengths_pick_list = PickList::new(
+                        WORD_LENGTHS,
+                        Some(pseudo.lengths),
+                        Message::MarkovLengthsChanged,
+                    )
+                    .text_size(15);
+                    let mut row = Row::new()
+                        .push(Text::new("Letters of context").size(14))
+                        .push(order_pick_list)
+                        .push(Text::new("Word lengths").size(14))
+                        .push(lengths_pick_list)
+                        .align_items(Alignment::Center)
+                        .spacing(10);
+                    if pseudo.corpus.is_some() {
+                        row = row.push(
+                            Button::new(Text::new("Use Built-in Words").size(14))
+                                .on_press(Message::MarkovCorpusCleared)
+                                .style(style::Button::Basic)
+                                .padding(6),
+                        );
+                    }
+                    words_section = words_section.push(row).push(
+                        Text::new(
+                            "Made-up words are learned from the language's word list, or from \
+                             any text file given below. More letters of context look more \
+                             like real words but repeat them more often.",
+                        )
+                        .size(14),
+                    );
+                }
+
+                if matches!(
+                    source,
+                    words::Source::Markov | words::Source::File | words::Source::Code
+                ) {
+                    let placeholder = match (&setting, source) {
+                        (words::Setting::File(path), words::Source::File)
+                        | (words::Setting::Code(path), words::Source::Code) => {
+                            path.display().to_string()
+                        }
+                        (
+                            words::Setting::Markov(markov::Setting {
+                                corpus: Some(path), ..
+                            }),
+                            words::Source::Markov,
+                        ) => path.display().to_string(),
+                        (_, words::Source::Markov) => {
+                            "Text file to learn words from (optional)".to_string()
+                        }
+                        (_, words::Source::Code) => {
+                            "Path to a source file or directory".to_string()
+                        }
+                        _ => "Path to a text file or word list".to_string(),
+                    };
+                    let mut path_input =
+                        TextInput::new(&placeholder, words_path, Message::WordsPathInput)
+                            .width(Length::Fill)
+                            .padding(6)
+                            .size(15);
+
+                    let mut accept =
+                        Button::new(centered_text("\u{2714}", 24, 20)).style(style::Button::Accept);
+                    if !words_path.trim().is_empty() {
+                        path_input = path_input.on_submit(Message::WordsPathAccept);
+                        accept = accept.on_press(Message::WordsPathAccept);
+                    }
+
+                    words_section =
+                        words_section.push(Row::new().push(path_input).push(accept).spacing(5));
+                    if *words_error {
+                        words_section = words_section.push(
+                            Text::new("Could not find that path")
+                                .size(14)
+                                .style(style::Text::Error),
+                        );
+                    }
+                }
+
+                let export_title = Text::new("Export").size(14).font(font::Font::Thin);
+                let mut export_input = TextInput::new(
+                    "File or folder to export this profile to",
+                    export_path,
+                    Message::ExportPathInput,
+                )
+                .width(Length::Fill)
+                .padding(6)
+                .size(15);
+
+                let mut export_button = Button::new(Text::new("Export").size(14))
+                    .style(style::Button::Basic)
+                    .padding(6);
+                if !export_path.trim().is_empty() {
+                    export_input = export_input.on_submit(Message::ExportAccept);
+                    export_button = export_button.on_press(Message::ExportAccept);
+                }
+
+                let mut export_section = Column::new()
+                    .padding([0, 0, 0, 6])
+                    .spacing(5)
+                    .push(export_title)
+                    .push(Row::new().push(export_input).push(export_button).spacing(5));
+                match exported {
+                    Some(Ok(path)) => {
+                        export_section = export_section
+                            .push(Text::new(format!("Exported to {}", path.display())).size(14));
+                    }
+                    Some(Err(error)) => {
+                        export_section = export_section
+                            .push(Text::new(error).size(14).style(style::Text::Error));
+                    }
+                    None => {}
+                }
+
+                let active = profiles.active();
+                let ghost_title = Text::new("Ghost").size(14).font(font::Font::Thin);
+                let ghost_kinds: Vec<GhostKind> = GHOST_KINDS
+                    .iter()
+                    .cloned()
+                    .filter(|&kind| kind != GhostKind::Line || active.ghost.kind() == kind)
+                    .collect();
+                let ghost_pick_list = PickList::new(
+                    ghost_kinds,
+                    Some(active.ghost.kind()),
+                    Message::GhostChanged,
+                )
+                .text_size(15);
+                let ghost_section = Column::new()
+                    .padding([0, 0, 0, 6])
+                    .spacing(5)
+                    .push(ghost_title)
+                    .push(ghost_pick_list)
+                    .push(
+                        Text::new(
+                            "Race a second cursor through each line. A past line to race \
+                             can be chosen in Replays.",
+                        )
+                        .size(14),
+                    );
+
+                let seed_title = Text::new("Seed").size(14).font(font::Font::Thin);
+                let seed_note = match active.seed {
+                    Some(seed) => format!(
+                        "Every session's first {} lines come from seed {}",
+                        SEEDED_LINES, seed
+                    ),
+                    None => format!(
+                        "This session's first {} lines come from seed {}",
+                        SEEDED_LINES,
+                        active.session.seed()
+                    ),
+                };
+                let seed_detail = "The same seed gives the same lines while the letters in \
+                     training and the words stay the same. Unlocking or locking a letter \
+                     ends them early, and later lines follow your weakest letters.";
+                let mut seed_input =
+                    TextInput::new("Seed to replay", seed_value, Message::SeedInput)
+                        .width(Length::Fill)
+                        .padding(6)
+                        .size(15);
+                let mut replay_button = Button::new(Text::new("Replay").size(14))
+                    .style(style::Button::Basic)
+                    .padding(6);
+                let mut keep_button = Button::new(Text::new("Always Use").size(14))
+                    .style(style::Button::Basic)
+                    .padding(6);
+                if !seed_value.trim().is_empty() {
+                    seed_input = seed_input.on_submit(Message::SeedReplay);
+                    replay_button = replay_button.on_press(Message::SeedReplay);
+                    keep_button = keep_button.on_press(Message::SeedKeep);
+                }
+
+                let mut seed_row = Row::new()
+                    .push(seed_input)
+                    .push(replay_button)
+                    .push(keep_button)
+                    .spacing(5);
+                if active.seed.is_some() {
+                    seed_row = seed_row.push(
+                        Button::new(Text::new("Clear").size(14))
+                            .style(style::Button::Basic)
+                            .on_press(Message::SeedClear)
+                            .padding(6),
+                    );
+                }
+
+                let mut seed_section = Column::new()
+                    .padding([0, 0, 0, 6])
+                    .spacing(5)
+                    .push(seed_title)
+                    .push(Text::new(seed_note).size(14))
+                    .push(Text::new(seed_detail).size(12))
+                    .push(seed_row);
+                if *seed_error {
+                    seed_section = seed_section.push(
+                        Text::new("A seed is a whole number")
+                            .size(14)
+                            .style(style::Text::Error),
+                    );
+                }
+
                 content = content
                     .push(rename_button)
                     .push(layout_section)
-                    .push(difficulty_section);
+                    .push(language_section)
+                    .push(difficulty_section)
+                    .push(letters_section)
+                    .push(words_section)
+                    .push(ghost_section)
+                    .push(seed_section)
+                    .push(export_section);
             }
         }
 
@@ -355,6 +1156,115 @@ impl Screen {
     }
 }
 
+/// Letters in training, which can be locked, and letters still to unlock,
+/// which can be unlocked or put in a new order
+fn letters_section<'a>(
+    active: &'a profile::Active,
+    order_value: &'a str,
+    lock_error: Option<&'a str>,
+) -> Element<'a, Message> {
+    let letter_buttons = |letters: Vec<char>, on_press: fn(char) -> Message| {
+        Column::with_children(
+            letters
+                .chunks(LETTERS_PER_ROW)
+                .map(|chunk| {
+                    Row::with_children(
+                        chunk
+                            .iter()
+                            .map(|&letter| {
+                                let label = Container::new(Text::new(letter.to_string()).size(14))
+                                    .width(14)
+                                    .center_x();
+                                Button::new(label)
+                                    .style(style::Button::Basic)
+                                    .on_press(on_press(letter))
+                                    .padding(4)
+                                    .into()
+                            })
+                            .collect(),
+                    )
+                    .spacing(4)
+                    .into()
+                })
+                .collect(),
+        )
+        .spacing(4)
+    };
+
+    let char_set = active.state.char_set();
+    let unlocked: Vec<char> = active
+        .layout
+        .letter_order(active.language)
+        .into_iter()
+        .chain(char_set.iter().cloned().sorted())
+        .filter(|letter| char_set.contains(letter))
+        .unique()
+        .collect();
+    let remaining = active.state.unlock_order(&active.layout, active.language);
+
+    let mut order_input = TextInput::new(
+        "Letters to unlock next, in order",
+        order_value,
+        Message::OrderInput,
+    )
+    .width(Length::Fill)
+    .padding(6)
+    .size(15);
+    let mut order_accept =
+        Button::new(centered_text("\u{2714}", 24, 20)).style(style::Button::Accept);
+    if !order_value.trim().is_empty() {
+        order_input = order_input.on_submit(Message::OrderAccept);
+        order_accept = order_accept.on_press(Message::OrderAccept);
+    }
+    let mut unlock_all = Button::new(Text::new("Unlock All").size(14))
+        .style(style::Button::Basic)
+        .padding(6);
+    if !remaining.is_empty() {
+        unlock_all = unlock_all.on_press(Message::UnlockAllPressed);
+    }
+
+    let mut section = Column::new()
+        .padding([0, 0, 0, 6])
+        .spacing(5)
+        .push(Text::new("Letters").size(14).font(font::Font::Thin))
+        .push(Text::new("In training. Press a letter to take it out.").size(14))
+        .push(letter_buttons(unlocked, Message::LetterLocked));
+    if let Some(error) = lock_error {
+        section = section.push(Text::new(error).size(14).style(style::Text::Error));
+    }
+    if !remaining.is_empty() {
+        section = section
+            .push(Text::new("Still to unlock, in order. Press a letter to unlock it now.").size(14))
+            .push(letter_buttons(remaining, Message::LetterUnlocked));
+    }
+
+    section
+        .push(
+            Row::new()
+                .push(order_input)
+                .push(order_accept)
+                .push(unlock_all)
+                .spacing(5),
+        )
+        .into()
+}
+
+/// Change how the active profile makes up pseudo-words, while it uses them
+fn change_markov(
+    profiles: &mut profile::List,
+    change: impl FnOnce(&mut markov::Setting),
+) -> Option<Event> {
+    let words::Setting::Markov(mut setting) = profiles.active().session.words_setting() else {
+        return None;
+    };
+    change(&mut setting);
+    profiles
+        .active_mut()
+        .set_words(words::Setting::Markov(setting));
+
+    Some(Event::Save)
+}
+
 fn centered_text(s: &str, size: u16, side: u16) -> Element<Message> {
     Container::new(Text::new(s).size(size))
         .width(side)
@@ -0,0 +1,234 @@
+use crate::data::profile;
+use crate::data::recording::{Ghost, Recording};
+use crate::data::training::visible;
+use crate::font;
+use crate::style;
+use crate::Element;
+
+use iced::widget::{Button, Column, Container, Row, Scrollable, Text};
+use iced::{Alignment, Length, Subscription};
+use itertools::{EitherOrBoth, Itertools};
+use time::{Duration, Instant};
+
+const CHAR_WIDTH: u16 = 10;
+/// Time between redraws while a line is playing
+const TICK_MS: u64 = 20;
+/// Characters of each line shown in the list of recordings
+const PREVIEW_CHARS: usize = 32;
+
+#[derive(Debug, Default)]
+pub struct State {
+    /// Index of the recording being shown, among the profile's recordings
+    selected: Option<usize>,
+    /// When playback started, while it is running
+    started: Option<Instant>,
+    /// How far into the line playback has got
+    elapsed: Duration,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    PlayPressed(usize),
+    RacePressed(usize),
+    StopPressed,
+    Tick,
+}
+
+pub enum Event {
+    Save,
+}
+
+impl State {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn update(&mut self, profiles: &mut profile::List, message: Message) -> Option<Event> {
+        match message {
+            Message::PlayPressed(index) => {
+                self.selected = Some(index);
+                self.started = Some(Instant::now());
+                self.elapsed = Duration::zero();
+            }
+            Message::RacePressed(index) => {
+                let active = profiles.active_mut();
+                if let Some(recording) = active.state.recordings().get(index) {
+                    active.ghost = Ghost::Line(recording.clone());
+
+                    return Some(Event::Save);
+                }
+            }
+            Message::StopPressed => {
+                self.started = None;
+            }
+            Message::Tick => {
+                let recording = self
+                    .selected
+                    .and_then(|index| profiles.active().state.recordings().get(index));
+                if let (Some(started), Some(recording)) = (self.started, recording) {
+                    self.elapsed = started.elapsed().min(recording.duration());
+                    if self.elapsed >= recording.duration() {
+                        self.started = None;
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    pub fn subscription(&self) -> Subscription<Message> {
+        if self.started.is_some() {
+            iced::time::every(std::time::Duration::from_millis(TICK_MS)).map(|_| Message::Tick)
+        } else {
+            Subscription::none()
+        }
+    }
+
+    pub fn view(&self, profiles: &profile::List) -> Element<Message> {
+        let active = profiles.active();
+        let recordings = active.state.recordings();
+        let title = Container::new(Text::new("Replays").size(18)).padding(6);
+
+        let mut content = Column::new()
+            .push(title)
+            .width(Length::Fill)
+            .spacing(20)
+            .padding(10);
+
+        if let Some(recording) = self.selected.and_then(|index| recordings.get(index)) {
+            content = content.push(self.player(recording));
+        }
+
+        let mut list = Column::new()
+            .padding([0, 0, 0, 6])
+            .spacing(5)
+            .push(Text::new("Recent Lines").size(14).font(font::Font::Thin));
+        if recordings.is_empty() {
+            list = list.push(Text::new("Lines you type are recorded here").size(14));
+        }
+        for (i, recording) in recordings.iter().enumerate().rev() {
+            let preview: String = recording
+                .text
+                .chars()
+                .take(PREVIEW_CHARS)
+                .map(visible)
+                .collect();
+            let playback = recording.playback(recording.duration());
+            let racing = matches!(&active.ghost, Ghost::Line(ghost) if ghost == recording);
+            let race: Element<_> = if racing {
+                Text::new("Racing").size(14).into()
+            } else {
+                Button::new(Text::new("Race").size(14))
+                    .on_press(Message::RacePressed(i))
+                    .style(style::Button::Basic)
+                    .into()
+            };
+            let row = Row::new()
+                .push(
+                    Text::new(recording.time.format("%Y-%m-%d %H:%M"))
+                        .size(14)
+                        .width(125),
+                )
+                .push(Text::new(preview).size(14).width(Length::Fill))
+                .push(
+                    Text::new(format!(
+                        "{} wpm, {} missed",
+                        recording
+                            .wpm()
+                            .map_or("-".to_string(), |wpm| format!("{:.0}", f64::from(wpm))),
+                        playback.misses
+                    ))
+                    .size(14),
+                )
+                .push(
+                    Button::new(Text::new("Play").size(14))
+                        .on_press(Message::PlayPressed(i))
+                        .style(style::Button::Basic),
+                )
+                .push(race)
+                .align_items(Alignment::Center)
+                .spacing(10);
+            list = list.push(row);
+        }
+
+        Scrollable::new(content.push(list))
+            .height(Length::Fill)
+            .into()
+    }
+
+    /// The line as it stood at the current point of playback
+    fn player(&self, recording: &Recording) -> Element<Message> {
+        let playback = recording.playback(self.elapsed);
+        let text: Vec<char> = recording.text.chars().collect();
+        let (typed, remaining) = text.split_at(playback.typed.len().min(text.len()));
+
+        let line = Row::with_children(
+            typed
+                .iter()
+                .zip(playback.typed.iter())
+                .map(|(&c, &missed)| {
+                    Text::new(visible(c).to_string())
+                        .width(CHAR_WIDTH)
+                        .font(font::Font::Thin)
+                        .style(if missed {
+                            style::Text::Miss
+                        } else {
+                            style::Text::Default
+                        })
+                })
+                .chain(
+                    playback
+                        .errors
+                        .iter()
+                        .zip_longest(remaining.iter())
+                        .enumerate()
+                        .map(|(i, result)| match result {
+                            EitherOrBoth::Left(e) | EitherOrBoth::Both(e, _) => {
+                                let c = if *e == ' ' { '\u{2591}' } else { visible(*e) };
+                                Text::new(c.to_string())
+                                    .width(CHAR_WIDTH)
+                                    .font(font::Font::Medium)
+                                    .style(style::Text::Error)
+                            }
+                            EitherOrBoth::Right(t) if i == 0 => Text::new(visible(*t).to_string())
+                                .width(CHAR_WIDTH)
+                                .style(style::Text::Target),
+                            EitherOrBoth::Right(t) => {
+                                Text::new(visible(*t).to_string()).width(CHAR_WIDTH)
+                            }
+                        }),
+                )
+                .map(|text| text.into())
+                .collect(),
+        );
+
+        let button = if self.started.is_some() {
+            Button::new(Text::new("Stop").size(14)).on_press(Message::StopPressed)
+        } else {
+            Button::new(Text::new("Play Again").size(14))
+                .on_press(Message::PlayPressed(self.selected.unwrap_or_default()))
+        }
+        .style(style::Button::Basic);
+
+        let controls = Row::new()
+            .push(
+                Text::new(format!(
+                    "{:.1} s of {:.1} s",
+                    self.elapsed.as_seconds_f64(),
+                    recording.duration().as_seconds_f64()
+                ))
+                .size(14)
+                .width(Length::Fill),
+            )
+            .push(button)
+            .align_items(Alignment::Center)
+            .spacing(10);
+
+        Column::new()
+            .padding([0, 0, 0, 6])
+            .spacing(10)
+            .push(line)
+            .push(controls)
+            .into()
+    }
+}
@@ -0,0 +1,674 @@
+use crate::data::export::Statistics;
+use crate::data::keyboard::Geometry;
+use crate::data::profile;
+use crate::data::training::{Event, REVIEW_AFTER_DAYS};
+use crate::font;
+use crate::style;
+use crate::style::alpha;
+use crate::Element;
+
+use iced::alignment;
+use iced::mouse;
+use iced::widget::canvas::{self, Canvas, Frame, Path, Stroke};
+use iced::widget::{Button, Column, Container, Row, Scrollable, Text, TextInput};
+use iced::{Alignment, Length, Point, Rectangle, Size};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use time::OffsetDateTime;
+
+type Renderer = iced::Renderer<style::Theme>;
+
+/// Most points drawn in a single chart; longer histories are averaged
+const MAX_POINTS: usize = 200;
+const CHART_HEIGHT: u16 = 120;
+const SPARKLINE_HEIGHT: u16 = 24;
+const SPARKLINE_WIDTH: u16 = 90;
+const SPARKLINES_PER_ROW: usize = 4;
+const TIMELINE_HEIGHT: u16 = 90;
+const KEYBOARD_HEIGHT: u16 = 130;
+/// Horizontal offset of each row on a staggered keyboard, in keys, for the
+/// number row down to the bottom row
+const STAGGER: &[f32] = &[0.0, 0.5, 0.75, 1.25];
+/// Space between keys, as a fraction of a key
+const KEY_GAP: f32 = 0.1;
+/// Space left of the chart for axis labels
+const LABEL_WIDTH: f32 = 40.0;
+const LABEL_SIZE: f32 = 11.0;
+
+#[derive(Debug, Default)]
+pub struct State {
+    export_path: String,
+    /// Files written by the last export, or why it failed
+    exported: Option<Result<Vec<PathBuf>, String>>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    ExportAccept,
+    ExportPathInput(String),
+    Exported(Result<Vec<PathBuf>, String>),
+}
+
+pub enum Event {
+    Export(Statistics, PathBuf),
+}
+
+/// Line chart of evenly spaced values
+struct Chart {
+    values: Vec<f32>,
+    /// Whether to draw axes and labels, or just the line
+    labels: bool,
+}
+
+/// Timeline of when each letter was unlocked
+struct Timeline {
+    unlocks: Vec<(OffsetDateTime, char)>,
+}
+
+/// Keys of the profile's layout, coloured by a metric
+struct Keyboard {
+    rows: Vec<Vec<Key>>,
+    geometry: Geometry,
+}
+
+struct Key {
+    label: char,
+    unlocked: bool,
+    /// Position on the metric colour scale, from 0 to 1, once measured
+    value: Option<f32>,
+}
+
+impl State {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn update(&mut self, profiles: &profile::List, message: Message) -> Option<Event> {
+        match message {
+            Message::ExportAccept => {
+                let active = profiles.active();
+                let statistics =
+                    Statistics::new(&active.name, &active.state, &active.layout, active.language);
+                return Some(Event::Export(
+                    statistics,
+                    PathBuf::from(self.export_path.trim()),
+                ));
+            }
+            Message::ExportPathInput(path) => {
+                self.export_path = path;
+                self.exported = None;
+            }
+            Message::Exported(result) => {
+                self.exported = Some(result);
+            }
+        }
+        None
+    }
+
+    pub fn view(&self, profiles: &profile::List) -> Element<Message> {
+        let active = profiles.active();
+        let state = &active.state;
+        let history = state.history();
+        let totals = state.totals();
+
+        let title = Container::new(Text::new("Statistics").size(18)).padding(6);
+
+        let minutes = totals.time_training.whole_minutes();
+        let summary = Column::new()
+            .push(Text::new(format!("{} lines typed", totals.lines_typed)).size(14))
+            .push(Text::new(format!("{} characters typed", totals.characters_typed)).size(14))
+            .push(Text::new(format!("{}h {}m training", minutes / 60, minutes % 60)).size(14))
+            .push(Text::new(format!("{} wpm top speed", f64::from(totals.top_speed_wpm))).size(14))
+            .spacing(5);
+
+        let speed = downsample(
+            history
+                .iter()
+                .map(|record| f64::from(record.wpm) as f32)
+                .collect(),
+        );
+        let accuracy = downsample(
+            history
+                .iter()
+                .map(|record| 100.0 * record.accuracy)
+                .collect(),
+        );
+
+        let letters: Vec<char> = active
+            .layout
+            .letter_order(active.language)
+            .into_iter()
+            .filter(|letter| state.timings.contains_key(letter))
+            .collect();
+        let sparklines = Column::with_children(
+            letters
+                .chunks(SPARKLINES_PER_ROW)
+                .map(|chunk| {
+                    Row::with_children(
+                        chunk
+                            .iter()
+                            .map(|&letter| {
+                                let mut values: Vec<f32> = state
+                                    .events()
+                                    .iter()
+                                    .filter_map(|event| match event {
+                                        Event::Checkpoint { letters, .. } => letters
+                                            .get(&letter)
+                                            .map(|rollup| f64::from(rollup.wpm_harmonic_mean)),
+                                        _ => None,
+                                    })
+                                    .map(|wpm| wpm as f32)
+                                    .collect();
+                                if let Some(stats) = state.timings.get(&letter) {
+                                    values.push(f64::from(stats.wpm_harmonic_mean) as f32);
+                                }
+
+                                Row::new()
+                                    .push(
+                                        Text::new(letter.to_string())
+                                            .font(font::Font::Light)
+                                            .size(12)
+                                            .width(12),
+                                    )
+                                    .push(chart(values, false, SPARKLINE_WIDTH, SPARKLINE_HEIGHT))
+                                    .align_items(Alignment::Center)
+                                    .spacing(5)
+                                    .into()
+                            })
+                            .collect(),
+                    )
+                    .spacing(20)
+                    .into()
+                })
+                .collect(),
+        )
+        .spacing(5);
+
+        let targets = active.targets();
+        let accuracy_metric = targets.accuracy_metric();
+        let wpm_metric = targets.wpm_metric();
+        let clean: HashMap<char, f32> = state.clean_letters().into_iter().collect();
+        let char_set = state.char_set();
+        let keyboard = |value: &dyn Fn(char) -> Option<f32>| {
+            let rows = active
+                .layout
+                .rows()
+                .into_iter()
+                .map(|row| {
+                    row.into_iter()
+                        .map(|label| Key {
+                            label,
+                            unlocked: char_set.contains(&label),
+                            value: value(label),
+                        })
+                        .collect()
+                })
+                .collect();
+            Canvas::new(Keyboard {
+                rows,
+                geometry: active.layout.geometry(),
+            })
+            .width(Length::Fill)
+            .height(KEYBOARD_HEIGHT)
+        };
+        let accuracy_keyboard =
+            keyboard(&|c| clean.get(&c).map(|&clean| accuracy_metric.value(clean)));
+        let speed_keyboard = keyboard(&|c| {
+            state
+                .timings
+                .get(&c)
+                .map(|stats| f64::from(stats.wpm_harmonic_mean) as f32)
+                .filter(|&wpm| wpm > 0.0)
+                .map(|wpm| wpm_metric.value(wpm))
+        });
+        let keyboards = Row::new()
+            .push(
+                Column::new()
+                    .push(Text::new("Accuracy").size(12).font(font::Font::Light))
+                    .push(accuracy_keyboard)
+                    .spacing(5),
+            )
+            .push(
+                Column::new()
+                    .push(Text::new("Speed").size(12).font(font::Font::Light))
+                    .push(speed_keyboard)
+                    .spacing(5),
+            )
+            .spacing(20);
+
+        let fingers = active.layout.fingers();
+        let mut finger_rows = Column::new().spacing(5);
+        for stats in state.finger_stats(&fingers) {
+            let clean_style = if stats.clean < targets.accuracy {
+                style::Text::Error
+            } else {
+                style::Text::Default
+            };
+            finger_rows = finger_rows.push(
+                Row::new()
+                    .push(Text::new(stats.finger.to_string()).size(14).width(110))
+                    .push(
+                        Text::new(stats.letters.iter().collect::<String>())
+                            .size(14)
+                            .font(font::Font::Light)
+                            .width(90),
+                    )
+                    .push(
+                        Text::new(format!("{:.0} wpm", f64::from(stats.wpm)))
+                            .size(14)
+                            .width(70),
+                    )
+                    .push(
+                        Text::new(format!("{:.0}% clean", 100.0 * stats.clean))
+                            .size(14)
+                            .style(clean_style),
+                    )
+                    .spacing(10),
+            );
+        }
+        let mut hands = Column::new().spacing(5);
+        for stats in state.hand_stats(&fingers) {
+            hands = hands.push(
+                Text::new(format!(
+                    "{} hand: {:.0}% of keys typed, {:.0} wpm, {:.0}% clean",
+                    stats.hand,
+                    100.0 * stats.share,
+                    f64::from(stats.wpm),
+                    100.0 * stats.clean
+                ))
+                .size(14),
+            );
+        }
+        let same_finger = match state.same_finger(&fingers) {
+            Some(same) => {
+                let (same, other) = (f64::from(same.same), f64::from(same.other));
+                format!(
+                    "Same-finger transitions: {:.0} wpm, against {:.0} wpm for the rest ({:+.0}%)",
+                    same,
+                    other,
+                    100.0 * (same - other) / other.max(1.0)
+                )
+            }
+            None => "Same-finger transitions: not enough samples yet".to_string(),
+        };
+        let finger_section = Column::new()
+            .push(finger_rows)
+            .push(hands)
+            .push(Text::new(same_finger).size(14))
+            .spacing(15);
+
+        let due = state.due_for_review(active.session.clock().now_utc());
+        let review: Element<_> = if due.is_empty() {
+            Text::new(format!(
+                "Every letter in training has been typed cleanly in the last {} days",
+                REVIEW_AFTER_DAYS
+            ))
+            .size(14)
+            .into()
+        } else {
+            Column::with_children(
+                due.into_iter()
+                    .map(|(letter, since)| {
+                        Text::new(format!(
+                            "{}  last typed cleanly {} days ago",
+                            letter,
+                            since.whole_days()
+                        ))
+                        .size(14)
+                        .into()
+                    })
+                    .collect(),
+            )
+            .spacing(5)
+            .into()
+        };
+
+        let unlocks = state
+            .events()
+            .iter()
+            .filter_map(|event| match event {
+                Event::Unlock { letter, time, .. } => Some((*time, *letter)),
+                _ => None,
+            })
+            .collect();
+        let timeline = Canvas::new(Timeline { unlocks })
+            .width(Length::Fill)
+            .height(TIMELINE_HEIGHT);
+
+        let mut export_input = TextInput::new(
+            "Folder to write CSV and JSON files to",
+            &self.export_path,
+            Message::ExportPathInput,
+        )
+        .width(Length::Fill)
+        .padding(6)
+        .size(15);
+        let mut export_button = Button::new(Text::new("Export").size(14))
+            .style(style::Button::Basic)
+            .padding(6);
+        if !self.export_path.trim().is_empty() {
+            export_input = export_input.on_submit(Message::ExportAccept);
+            export_button = export_button.on_press(Message::ExportAccept);
+        }
+        let mut export = Column::new()
+            .push(Row::new().push(export_input).push(export_button).spacing(5))
+            .spacing(5);
+        match &self.exported {
+            Some(Ok(paths)) => {
+                for path in paths {
+                    export = export.push(Text::new(format!("Wrote {}", path.display())).size(14));
+                }
+            }
+            Some(Err(error)) => {
+                export = export.push(Text::new(error).size(14).style(style::Text::Error));
+            }
+            None => {}
+        }
+
+        let content = Column::new()
+            .push(title)
+            .push(section("Totals", summary))
+            .push(section("Keyboard", keyboards))
+            .push(section("Fingers", finger_section))
+            .push(section("Due for Review", review))
+            .push(section(
+                "Speed (wpm)",
+                chart(speed, true, Length::Fill, CHART_HEIGHT),
+            ))
+            .push(section(
+                "Accuracy (%)",
+                chart(accuracy, true, Length::Fill, CHART_HEIGHT),
+            ))
+            .push(section("Letter Speed", sparklines))
+            .push(section("Unlocks", timeline))
+            .push(section("Export", export))
+            .width(Length::Fill)
+            .spacing(20)
+            .padding(10);
+
+        Scrollable::new(content).height(Length::Fill).into()
+    }
+}
+
+impl<Message> canvas::Program<Message, Renderer> for Chart {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        renderer: &Renderer,
+        theme: &style::Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<canvas::Geometry> {
+        let palette = theme.palette();
+        let mut frame = Frame::new(renderer, bounds.size());
+
+        let left = if self.labels { LABEL_WIDTH } else { 0.0 };
+        let width = frame.width() - left;
+        let height = frame.height();
+
+        if self.values.len() < 2 {
+            if self.labels {
+                frame.fill_text(canvas::Text {
+                    content: "Not enough data yet".to_string(),
+                    position: frame.center(),
+                    color: alpha(palette.text, 0.5),
+                    size: LABEL_SIZE,
+                    font: font::Font::Light.into(),
+                    horizontal_alignment: alignment::Horizontal::Center,
+                    vertical_alignment: alignment::Vertical::Center,
+                    ..canvas::Text::default()
+                });
+            }
+            return vec![frame.into_geometry()];
+        }
+
+        let min = self.values.iter().cloned().fold(f32::INFINITY, f32::min);
+        let max = self
+            .values
+            .iter()
+            .cloned()
+            .fold(f32::NEG_INFINITY, f32::max);
+        let range = (max - min).max(1.0);
+        let point = |i: usize, value: f32| {
+            Point::new(
+                left + width * i as f32 / (self.values.len() - 1) as f32,
+                height - height * (value - min) / range,
+            )
+        };
+
+        if self.labels {
+            let axes = Path::new(|builder| {
+                builder.move_to(Point::new(left, 0.0));
+                builder.line_to(Point::new(left, height));
+                builder.line_to(Point::new(left + width, height));
+            });
+            frame.stroke(
+                &axes,
+                Stroke::default()
+                    .with_color(alpha(palette.text, 0.15))
+                    .with_width(1.0),
+            );
+
+            for (value, y, vertical_alignment) in [
+                (max, 0.0, alignment::Vertical::Top),
+                (min, height, alignment::Vertical::Bottom),
+            ] {
+                frame.fill_text(canvas::Text {
+                    content: format!("{:.0}", value),
+                    position: Point::new(left - 5.0, y),
+                    color: alpha(palette.text, 0.5),
+                    size: LABEL_SIZE,
+                    font: font::Font::Light.into(),
+                    horizontal_alignment: alignment::Horizontal::Right,
+                    vertical_alignment,
+                    ..canvas::Text::default()
+                });
+            }
+        }
+
+        let line = Path::new(|builder| {
+            builder.move_to(point(0, self.values[0]));
+            for (i, &value) in self.values.iter().enumerate().skip(1) {
+                builder.line_to(point(i, value));
+            }
+        });
+        frame.stroke(
+            &line,
+            Stroke::default().with_color(palette.target).with_width(1.5),
+        );
+
+        vec![frame.into_geometry()]
+    }
+}
+
+impl<Message> canvas::Program<Message, Renderer> for Timeline {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        renderer: &Renderer,
+        theme: &style::Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<canvas::Geometry> {
+        let palette = theme.palette();
+        let mut frame = Frame::new(renderer, bounds.size());
+
+        let baseline = frame.height() - LABEL_SIZE - 5.0;
+        let width = frame.width() - 2.0 * LABEL_SIZE;
+        let axis = Path::line(
+            Point::new(LABEL_SIZE, baseline),
+            Point::new(LABEL_SIZE + width, baseline),
+        );
+        frame.stroke(
+            &axis,
+            Stroke::default()
+                .with_color(alpha(palette.text, 0.15))
+                .with_width(1.0),
+        );
+
+        let start = self.unlocks.iter().map(|(time, _)| *time).min();
+        let end = OffsetDateTime::now_utc();
+        let Some(start) = start else {
+            return vec![frame.into_geometry()];
+        };
+        let span = (end - start).as_seconds_f32().max(1.0);
+
+        for (time, x, horizontal_alignment) in [
+            (start, LABEL_SIZE, alignment::Horizontal::Left),
+            (end, LABEL_SIZE + width, alignment::Horizontal::Right),
+        ] {
+            frame.fill_text(canvas::Text {
+                content: time.date().format("%Y-%m-%d"),
+                position: Point::new(x, frame.height()),
+                color: alpha(palette.text, 0.5),
+                size: LABEL_SIZE,
+                font: font::Font::Light.into(),
+                horizontal_alignment,
+                vertical_alignment: alignment::Vertical::Bottom,
+                ..canvas::Text::default()
+            });
+        }
+
+        // Letters unlocked close together are stacked so they stay legible
+        let mut last_x = f32::NEG_INFINITY;
+        let mut level = 0;
+        for (time, letter) in &self.unlocks {
+            let x = LABEL_SIZE + width * (*time - start).as_seconds_f32() / span;
+            if x - last_x < LABEL_SIZE {
+                level += 1;
+            } else {
+                level = 0;
+                last_x = x;
+            }
+
+            let tick = Path::rectangle(Point::new(x - 0.5, baseline - 4.0), Size::new(1.0, 8.0));
+            frame.fill(&tick, alpha(palette.text, 0.5));
+            frame.fill_text(canvas::Text {
+                content: letter.to_string(),
+                position: Point::new(x, baseline - 6.0 - level as f32 * (LABEL_SIZE + 2.0)),
+                color: palette.target,
+                size: LABEL_SIZE + 1.0,
+                font: font::Font::Medium.into(),
+                horizontal_alignment: alignment::Horizontal::Center,
+                vertical_alignment: alignment::Vertical::Bottom,
+                ..canvas::Text::default()
+            });
+        }
+
+        vec![frame.into_geometry()]
+    }
+}
+
+impl<Message> canvas::Program<Message, Renderer> for Keyboard {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        renderer: &Renderer,
+        theme: &style::Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<canvas::Geometry> {
+        let palette = theme.palette();
+        let mut frame = Frame::new(renderer, bounds.size());
+
+        // Rows line up with the bottom of the stagger, as layouts without a
+        // number row start from the top letter row
+        let skipped = STAGGER.len().saturating_sub(self.rows.len());
+        let offset = |row: usize| match self.geometry {
+            Geometry::Staggered => STAGGER.get(skipped + row).cloned().unwrap_or_default(),
+            Geometry::Ortholinear => 0.0,
+        };
+        let columns = self
+            .rows
+            .iter()
+            .enumerate()
+            .map(|(i, keys)| offset(i) + keys.len() as f32)
+            .fold(1.0, f32::max);
+        let unit = (frame.width() / columns).min(frame.height() / self.rows.len().max(1) as f32);
+        let size = Size::new(unit * (1.0 - KEY_GAP), unit * (1.0 - KEY_GAP));
+
+        for (i, keys) in self.rows.iter().enumerate() {
+            for (j, key) in keys.iter().enumerate() {
+                let top_left = Point::new(
+                    unit * (offset(i) + j as f32 + KEY_GAP / 2.0),
+                    unit * (i as f32 + KEY_GAP / 2.0),
+                );
+                let rectangle = Path::rectangle(top_left, size);
+
+                let label_color = match key.value {
+                    Some(value) if key.unlocked => {
+                        frame.fill(&rectangle, palette.metric(value));
+                        palette.bg
+                    }
+                    _ => {
+                        let emphasis = if key.unlocked { 0.5 } else { 0.15 };
+                        frame.stroke(
+                            &rectangle,
+                            Stroke::default()
+                                .with_color(alpha(palette.text, emphasis))
+                                .with_width(1.0),
+                        );
+                        alpha(palette.text, 2.0 * emphasis)
+                    }
+                };
+                frame.fill_text(canvas::Text {
+                    content: key.label.to_string(),
+                    position: Point::new(
+                        top_left.x + size.width / 2.0,
+                        top_left.y + size.height / 2.0,
+                    ),
+                    color: label_color,
+                    size: (unit * 0.45).min(LABEL_SIZE + 3.0),
+                    font: font::Font::Medium.into(),
+                    horizontal_alignment: alignment::Horizontal::Center,
+                    vertical_alignment: alignment::Vertical::Center,
+                    ..canvas::Text::default()
+                });
+            }
+        }
+
+        vec![frame.into_geometry()]
+    }
+}
+
+fn chart<'a, Message: 'a>(
+    values: Vec<f32>,
+    labels: bool,
+    width: impl Into<Length>,
+    height: u16,
+) -> Element<'a, Message> {
+    Canvas::new(Chart { values, labels })
+        .width(width)
+        .height(height)
+        .into()
+}
+
+fn section<'a, Message: 'a>(
+    title: &str,
+    content: impl Into<Element<'a, Message>>,
+) -> Element<'a, Message> {
+    Column::new()
+        .push(Text::new(title.to_string()).size(14).font(font::Font::Thin))
+        .push(content)
+        .padding([0, 0, 0, 6])
+        .spacing(5)
+        .into()
+}
+
+/// Average values into at most `MAX_POINTS` evenly sized buckets
+fn downsample(values: Vec<f32>) -> Vec<f32> {
+    if values.len() <= MAX_POINTS {
+        return values;
+    }
+    let size = (values.len() as f32 / MAX_POINTS as f32).ceil() as usize;
+    values
+        .chunks(size)
+        .map(|chunk| chunk.iter().sum::<f32>() / chunk.len() as f32)
+        .collect()
+}
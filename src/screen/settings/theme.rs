@@ -50,22 +50,22 @@ impl State {
                         .push(
                             Text::new("\u{25a0}")
                                 .size(18)
-                                .style(style::Text::Override(th.target)),
+                                .style(style::Text::Override(th.target.into())),
                         )
                         .push(
                             Text::new("\u{25a0}")
                                 .size(18)
-                                .style(style::Text::Override(th.miss)),
+                                .style(style::Text::Override(th.miss.into())),
                         )
                         .push(
                             Text::new("\u{25a0}")
                                 .size(18)
-                                .style(style::Text::Override(th.error)),
+                                .style(style::Text::Override(th.error.into())),
                         )
                         .push(
                             Text::new(th.name)
                                 .size(16)
-                                .style(style::Text::Override(th.text)),
+                                .style(style::Text::Override(th.text.into())),
                         )
                         .spacing(5)
                         .align_items(Alignment::Center);
@@ -79,7 +79,7 @@ impl State {
                     Container::new(
                         Button::new(content)
                             .on_press(Message::ThemePressed(i))
-                            .style(style::Button::ThemePreview(th.clone()))
+                            .style(style::Button::ThemePreview(th.clone().into()))
                             .width(Length::Fill)
                             .padding([3, 7, 3, 7]),
                     )
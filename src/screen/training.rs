@@ -1,12 +1,13 @@
 use crate::data::profile;
-use crate::data::training::{Difficulty, TriplePoint, CHARS_PER_LINE, MAX_ERRORS, MIN_CLEAN_PCT};
+use crate::data::recording::Ghost;
+use crate::data::training::{visible, Targets, TriplePoint, CHARS_PER_LINE, MAX_ERRORS};
 use crate::font;
 use crate::style;
 use crate::Element;
 
 use iced::alignment::{self, Alignment};
 use iced::keyboard::{self, KeyCode};
-use iced::widget::{Button, Column, Container, Row, Space, Text};
+use iced::widget::{Button, Column, Container, Row, Scrollable, Space, Text};
 use iced::{Command, Length, Subscription};
 use itertools::{EitherOrBoth, Itertools};
 
@@ -20,6 +21,9 @@ pub struct State {
 #[derive(Debug, Clone)]
 pub enum Message {
     KeyboardEvent(iced::keyboard::Event),
+    TestPressed,
+    /// Time to move the ghost along
+    Tick,
     UserButtonPressed,
     WindowFocused,
     WindowUnocused,
@@ -28,27 +32,27 @@ pub enum Message {
 pub enum Event {
     Save,
     Settings,
+    /// Take a typing test
+    Test,
 }
 
 const CHAR_WIDTH: u16 = 10;
 const ROW_CHARS: u16 = (CHARS_PER_LINE + MAX_ERRORS - 1) as u16;
 const ROW_WIDTH: u16 = CHAR_WIDTH * ROW_CHARS;
-const ROW_ERROR_WIDTH: u16 = (MAX_ERRORS - 1) as u16 * CHAR_WIDTH;
 const LINE_SPACE: u16 = 10;
 const STATS_WIDTH: u16 = 75;
 pub const OVERALL_WIDTH: u16 = 2 * STATS_WIDTH + ROW_WIDTH;
+/// Number of slowest transitions listed beside the training lines
+const NUM_SLOW_BIGRAMS: usize = 10;
+/// Time between redraws while racing a ghost
+const GHOST_TICK_MS: u64 = 30;
 
 impl State {
-    pub fn new(difficulty: &Difficulty) -> Self {
+    pub fn new(targets: &Targets) -> Self {
         Self {
             modifiers: keyboard::Modifiers::default(),
-            accuracy_metric: TriplePoint::new(0.5, MIN_CLEAN_PCT, 0.975).unwrap_or_default(),
-            wpm_metric: TriplePoint::new(
-                10.0,
-                f64::from(difficulty.words_per_minute()) as f32,
-                60.0,
-            )
-            .unwrap_or_default(),
+            accuracy_metric: targets.accuracy_metric(),
+            wpm_metric: targets.wpm_metric(),
         }
     }
 
@@ -61,6 +65,7 @@ impl State {
             Message::KeyboardEvent(keyboard_event) => {
                 self.handle_keyboard(profiles, keyboard_event)
             }
+            Message::TestPressed => Some((Command::none(), Event::Test)),
             Message::UserButtonPressed => Some((Command::none(), Event::Settings)),
             _ => None,
         }
@@ -73,7 +78,7 @@ impl State {
                 .hits
                 .iter()
                 .map(|hit| {
-                    Text::new(hit.target().to_string())
+                    Text::new(visible(hit.target()).to_string())
                         .width(CHAR_WIDTH)
                         .font(font::Font::Thin)
                         .style(if hit.is_dirty() {
@@ -93,13 +98,15 @@ impl State {
                         )
                         .map(|result| match result {
                             EitherOrBoth::Left(e) | EitherOrBoth::Both(e, _) => {
-                                let c = if *e == ' ' { '\u{2591}' } else { *e };
+                                let c = if *e == ' ' { '\u{2591}' } else { visible(*e) };
                                 Text::new(c.to_string())
                                     .width(CHAR_WIDTH)
                                     .font(font::Font::Medium)
                                     .style(style::Text::Error)
                             }
-                            EitherOrBoth::Right(t) => Text::new(t.to_string()).width(CHAR_WIDTH),
+                            EitherOrBoth::Right(t) => {
+                                Text::new(visible(*t).to_string()).width(CHAR_WIDTH)
+                            }
                         }),
                 )
                 .map(|text| text.into())
@@ -121,11 +128,30 @@ impl State {
             Space::with_height(LINE_SPACE).into()
         };
 
-        let content_active = Column::new()
+        let mut content_active = Column::new()
             .width(ROW_WIDTH)
             .push(active_line)
             .push(target_indicator);
 
+        let session = profiles.session();
+        let elapsed = session.line_elapsed().unwrap_or_default();
+        if let Some(position) = profiles
+            .active()
+            .ghost
+            .position(profiles.active().state.best(), elapsed)
+        {
+            let line_length = session.hits.len() + 1 + session.targets.len();
+            content_active = content_active.push(Row::with_children(vec![
+                Space::with_width(position.min(line_length) as u16 * CHAR_WIDTH).into(),
+                Text::new("\u{2015}")
+                    .width(CHAR_WIDTH)
+                    .height(LINE_SPACE)
+                    .vertical_alignment(alignment::Vertical::Center)
+                    .style(style::Text::Ghost)
+                    .into(),
+            ]));
+        }
+
         let content_next = Column::with_children(
             profiles
                 .session()
@@ -134,7 +160,7 @@ impl State {
                 .map(|line| {
                     Row::with_children(
                         line.chars()
-                            .map(|c| Text::new(c.to_string()).width(CHAR_WIDTH).into())
+                            .map(|c| Text::new(visible(c).to_string()).width(CHAR_WIDTH).into())
                             .collect(),
                     )
                     .into()
@@ -144,8 +170,7 @@ impl State {
         .spacing(LINE_SPACE)
         .width(ROW_WIDTH);
 
-        let training = Column::with_children(vec![content_active.into(), content_next.into()])
-            .padding([0, STATS_WIDTH.saturating_sub(ROW_ERROR_WIDTH), 0, 0]);
+        let training = Column::with_children(vec![content_active.into(), content_next.into()]);
         let training = Container::new(training)
             .width(Length::Fill)
             .height(Length::Fill)
@@ -162,7 +187,11 @@ impl State {
                 .map(|(ch, val)| {
                     let stats = profiles.active().state.timings.get(ch);
                     let mut row = Row::new()
-                        .push(Text::new(ch.to_string()).font(font::Font::Light).size(12))
+                        .push(
+                            Text::new(visible(*ch).to_string())
+                                .font(font::Font::Light)
+                                .size(12),
+                        )
                         .push(
                             Text::new("\u{25a0}")
                                 .style(style::Text::Metric(self.accuracy_metric.value(*val)))
@@ -188,10 +217,48 @@ impl State {
         .width(STATS_WIDTH)
         .spacing(2)
         .padding(5);
+        let letter_stats = Scrollable::new(letter_stats).height(Length::Fill);
+
+        let state = &profiles.active().state;
+        let bigram_stats = Column::with_children(
+            state
+                .slowest_bigrams(NUM_SLOW_BIGRAMS)
+                .into_iter()
+                .map(|(bigram, stats)| {
+                    let clean = state.bigram_clean(&bigram).unwrap_or_default();
+                    let wpm = f64::from(stats.wpm_harmonic_mean) as f32;
+                    Row::new()
+                        .push(
+                            Text::new(bigram.to_string())
+                                .font(font::Font::Light)
+                                .size(12),
+                        )
+                        .push(
+                            Text::new("\u{25a0}")
+                                .style(style::Text::Metric(self.accuracy_metric.value(clean)))
+                                .font(font::Font::Light)
+                                .size(16),
+                        )
+                        .push(
+                            Text::new("\u{25a0}")
+                                .style(style::Text::Metric(self.wpm_metric.value(wpm)))
+                                .font(font::Font::Light)
+                                .size(16),
+                        )
+                        .align_items(Alignment::Center)
+                        .spacing(5)
+                        .into()
+                })
+                .collect(),
+        )
+        .width(STATS_WIDTH)
+        .spacing(2)
+        .padding(5);
 
         let content = Row::new()
             .push(letter_stats)
             .push(training)
+            .push(bigram_stats)
             .width(Length::Fill)
             .height(Length::Fill);
 
@@ -207,9 +274,16 @@ impl State {
             .style(style::Button::Text)
             .padding(10);
 
+        let test_button = Button::new(Text::new("Typing Test").size(14))
+            .on_press(Message::TestPressed)
+            .style(style::Button::Text)
+            .padding(10);
+
         let footer = Row::new()
+            .push(test_button)
             .push(Space::with_width(Length::Fill))
-            .push(settings_button);
+            .push(settings_button)
+            .align_items(Alignment::End);
 
         Column::with_children(vec![content.into(), footer.into()]).into()
     }
@@ -226,17 +300,9 @@ impl State {
             }
 
             keyboard::Event::KeyPressed { key_code, .. } => match key_code {
-                KeyCode::Space => {
-                    if let Some(line) = profiles.session_mut().apply_char(' ') {
-                        if let Some(words) = profiles.active_mut().add_line(line) {
-                            profiles.session_mut().update_words(words);
-                        }
-                        let profile = profiles.active_mut();
-                        profile.session.fill_next_lines(&profile.state);
-                        return Some((Command::none(), Event::Save));
-                    }
-                    None
-                }
+                KeyCode::Space => apply_char(profiles, ' '),
+                KeyCode::Enter => apply_char(profiles, '\n'),
+                KeyCode::Tab => apply_char(profiles, '\t'),
                 KeyCode::Escape => None,
                 KeyCode::Backspace => {
                     profiles.session_mut().backspace();
@@ -245,28 +311,45 @@ impl State {
                 _ => None,
             },
             keyboard::Event::CharacterReceived(c)
-                if c.is_alphanumeric() && !self.modifiers.command() =>
+                if (c.is_alphanumeric() || c.is_ascii_punctuation())
+                    && !self.modifiers.command() =>
             {
-                if let Some(line) = profiles.session_mut().apply_char(c) {
-                    if let Some(words) = profiles.active_mut().add_line(line) {
-                        profiles.session_mut().update_words(words);
-                    }
-                    let profile = profiles.active_mut();
-                    profile.session.fill_next_lines(&profile.state);
-                    return Some((Command::none(), Event::Save));
-                }
-                None
+                apply_char(profiles, c)
             }
             _ => None,
         }
     }
 }
 
-pub fn subscription() -> Subscription<Message> {
+/// Type a character, moving on to the next line once this one is complete
+fn apply_char(profiles: &mut profile::List, c: char) -> Option<(Command<Message>, Event)> {
+    let line = profiles.session_mut().apply_char(c)?;
+    if let Some(words) = profiles.active_mut().add_line(line) {
+        profiles.session_mut().update_words(words);
+    }
+    let profile = profiles.active_mut();
+    profile
+        .session
+        .fill_next_lines(&profile.state, &profile.targets());
+
+    Some((Command::none(), Event::Save))
+}
+
+/// Keyboard and window events, plus a steady tick while a ghost is racing
+/// through the current line
+pub fn subscription(profiles: &profile::List) -> Subscription<Message> {
     use iced::event::{Event, Status};
     use iced::window::Event as WindowEvent;
 
-    iced::subscription::events_with(|event, status| {
+    let racing =
+        profiles.active().ghost != Ghost::Off && profiles.session().line_elapsed().is_some();
+    let tick = if racing {
+        iced::time::every(std::time::Duration::from_millis(GHOST_TICK_MS)).map(|_| Message::Tick)
+    } else {
+        Subscription::none()
+    };
+
+    let events = iced::subscription::events_with(|event, status| {
         if status == Status::Captured {
             return None;
         }
@@ -276,5 +359,7 @@ pub fn subscription() -> Subscription<Message> {
             Event::Window(WindowEvent::Unfocused) => Some(Message::WindowUnocused),
             _ => None,
         }
-    })
+    });
+
+    Subscription::batch(vec![events, tick])
 }
@@ -0,0 +1,331 @@
+use crate::data::profile;
+use crate::data::typing_test::{Length, Score, Test, LENGTHS};
+use crate::font;
+use crate::style;
+use crate::Element;
+
+use iced::alignment::Alignment;
+use iced::keyboard::{self, KeyCode};
+use iced::widget::{Button, Column, Container, PickList, Row, Space, Text};
+use iced::{Length as Size, Subscription};
+
+const CHAR_WIDTH: u16 = 10;
+/// Characters in each row of the text, which wraps between words
+const ROW_CHARS: usize = 60;
+const ROW_WIDTH: u16 = CHAR_WIDTH * ROW_CHARS as u16;
+/// Rows of text shown at once, starting with the one being typed
+const VISIBLE_ROWS: usize = 3;
+const LINE_SPACE: u16 = 10;
+/// Time between redraws while a test is running
+const TICK_MS: u64 = 100;
+/// Past results listed under the score
+const NUM_RECENT_TESTS: usize = 10;
+
+#[derive(Debug)]
+pub struct State {
+    modifiers: keyboard::Modifiers,
+    test: Test,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    BackPressed,
+    KeyboardEvent(iced::keyboard::Event),
+    LengthChanged(Length),
+    RestartPressed,
+    /// Time to update the countdown, and end the test once it runs out
+    Tick,
+}
+
+pub enum Event {
+    Exit,
+    Save,
+}
+
+impl State {
+    /// A new test of the same length as the last one taken
+    pub fn new(profiles: &profile::List) -> Self {
+        let length = profiles
+            .active()
+            .tests
+            .last()
+            .map(|score| score.length)
+            .unwrap_or_default();
+
+        Self {
+            modifiers: keyboard::Modifiers::default(),
+            test: new_test(profiles, length),
+        }
+    }
+
+    pub fn update(&mut self, profiles: &mut profile::List, message: Message) -> Option<Event> {
+        let score = match message {
+            Message::BackPressed => return Some(Event::Exit),
+            Message::KeyboardEvent(keyboard_event) => match keyboard_event {
+                keyboard::Event::ModifiersChanged(modifiers) => {
+                    self.modifiers = modifiers;
+                    None
+                }
+                keyboard::Event::KeyPressed { key_code, .. } => match key_code {
+                    KeyCode::Space => self.test.apply_char(' '),
+                    KeyCode::Tab => {
+                        self.test = new_test(profiles, self.test.length());
+                        None
+                    }
+                    KeyCode::Backspace => {
+                        self.test.backspace();
+                        None
+                    }
+                    _ => None,
+                },
+                keyboard::Event::CharacterReceived(c)
+                    if (c.is_alphanumeric() || c.is_ascii_punctuation())
+                        && !self.modifiers.command() =>
+                {
+                    self.test.apply_char(c)
+                }
+                _ => None,
+            },
+            Message::LengthChanged(length) => {
+                self.test = new_test(profiles, length);
+                None
+            }
+            Message::RestartPressed => {
+                self.test = new_test(profiles, self.test.length());
+                None
+            }
+            Message::Tick => self.test.tick(),
+        };
+
+        score.map(|score| {
+            profiles.active_mut().tests.push(score);
+            Event::Save
+        })
+    }
+
+    pub fn subscription(&self) -> Subscription<Message> {
+        use iced::event::{Event, Status};
+
+        let tick = if self.test.is_running() {
+            iced::time::every(std::time::Duration::from_millis(TICK_MS)).map(|_| Message::Tick)
+        } else {
+            Subscription::none()
+        };
+
+        let events = iced::subscription::events_with(|event, status| match (event, status) {
+            (Event::Keyboard(keyboard_event), Status::Ignored) => {
+                Some(Message::KeyboardEvent(keyboard_event))
+            }
+            _ => None,
+        });
+
+        Subscription::batch(vec![events, tick])
+    }
+
+    pub fn view(&self, profiles: &profile::List) -> Element<Message> {
+        let test = &self.test;
+
+        let length_pick_list =
+            PickList::new(LENGTHS, Some(test.length()), Message::LengthChanged).text_size(15);
+        let restart_button = Button::new(Text::new("Restart").size(14))
+            .on_press(Message::RestartPressed)
+            .style(style::Button::Basic)
+            .padding(6);
+        let back_button = Button::new(Text::new("Back to Training").size(14))
+            .on_press(Message::BackPressed)
+            .style(style::Button::Basic)
+            .padding(6);
+        let header = Row::new()
+            .push(length_pick_list)
+            .push(restart_button)
+            .push(Space::with_width(Size::Fill))
+            .push(back_button)
+            .align_items(Alignment::Center)
+            .spacing(10)
+            .padding(10);
+
+        let progress = match (test.length(), test.remaining()) {
+            (_, Some(remaining)) => format!("{:.0}", remaining.as_seconds_f64().ceil()),
+            (Length::Words(words), None) => format!("{} / {}", test.words_typed(), words),
+            (Length::Seconds(_), None) => String::new(),
+        };
+
+        let mut content = Column::new()
+            .push(Text::new(progress).size(18).style(style::Text::Target))
+            .push(self.text())
+            .width(ROW_WIDTH)
+            .spacing(20);
+        if let Some(score) = test.score() {
+            content = content.push(results(score, &profiles.active().tests));
+        } else if !test.is_running() {
+            content = content.push(
+                Text::new("The test starts with the first key. Tab starts over, Escape goes back.")
+                    .size(14),
+            );
+        }
+
+        let body = Container::new(content)
+            .width(Size::Fill)
+            .height(Size::Fill)
+            .center_x()
+            .center_y();
+
+        Column::new().push(header).push(body).into()
+    }
+
+    /// The rows of text around the typist, coloured by what has been typed
+    fn text(&self) -> Element<Message> {
+        let text = self.test.text();
+        let typed = self.test.typed();
+        let rows = wrap(text);
+        let current = rows
+            .iter()
+            .position(|row| typed.len() < row.end)
+            .unwrap_or(rows.len().saturating_sub(1));
+
+        Column::with_children(
+            rows.into_iter()
+                .skip(current)
+                .take(VISIBLE_ROWS)
+                .map(|row| {
+                    Row::with_children(
+                        row.map(|i| {
+                            let target = text[i];
+                            let text = match typed.get(i) {
+                                Some(&c) if c == target => Text::new(target.to_string())
+                                    .font(font::Font::Thin)
+                                    .style(style::Text::Default),
+                                // Show a missed space as a shaded block, so it
+                                // can be seen
+                                Some(_) => {
+                                    let c = if target == ' ' { '\u{2591}' } else { target };
+                                    Text::new(c.to_string())
+                                        .font(font::Font::Medium)
+                                        .style(style::Text::Error)
+                                }
+                                None if i == typed.len() => {
+                                    Text::new(target.to_string()).style(style::Text::Target)
+                                }
+                                None => Text::new(target.to_string()),
+                            };
+                            text.width(CHAR_WIDTH).into()
+                        })
+                        .collect(),
+                    )
+                    .into()
+                })
+                .collect(),
+        )
+        .spacing(LINE_SPACE)
+        .into()
+    }
+}
+
+/// A fresh test for the active profile
+fn new_test(profiles: &profile::List, length: Length) -> Test {
+    let active = profiles.active();
+    Test::new(length, &active.layout, active.language, rand::random())
+}
+
+/// Split text into rows of at most `ROW_CHARS` characters, breaking after
+/// spaces where possible. Returns the range of indices in each row.
+fn wrap(text: &[char]) -> Vec<std::ops::Range<usize>> {
+    let mut rows = Vec::new();
+    let mut start = 0;
+    while start < text.len() {
+        let mut end = (start + ROW_CHARS).min(text.len());
+        if end < text.len() {
+            if let Some(space) = text[start..end].iter().rposition(|&c| c == ' ') {
+                end = start + space + 1;
+            }
+        }
+        rows.push(start..end);
+        start = end;
+    }
+    rows
+}
+
+/// The score of a finished test, with recent results of the same length
+fn results<'a>(score: &Score, tests: &[Score]) -> Element<'a, Message> {
+    let headline = Row::new()
+        .push(stat(
+            "net wpm",
+            format!("{:.0}", f64::from(score.net_wpm())),
+        ))
+        .push(stat(
+            "gross wpm",
+            format!("{:.0}", f64::from(score.gross_wpm())),
+        ))
+        .push(stat(
+            "accuracy",
+            format!("{:.1}%", 100.0 * score.accuracy()),
+        ))
+        .push(stat(
+            "characters",
+            format!("{} ({} wrong)", score.characters, score.errors),
+        ))
+        .spacing(30);
+
+    let same_length: Vec<&Score> = tests
+        .iter()
+        .filter(|test| test.length == score.length)
+        .collect();
+    let best = same_length
+        .iter()
+        .map(|test| f64::from(test.net_wpm()))
+        .fold(0.0, f64::max);
+
+    let mut history = Column::new()
+        .push(
+            Text::new(format!(
+                "{}: best {:.0} net wpm over {} tests",
+                score.length,
+                best,
+                same_length.len()
+            ))
+            .size(14)
+            .font(font::Font::Thin),
+        )
+        .spacing(5);
+    for test in same_length.iter().rev().take(NUM_RECENT_TESTS) {
+        history = history.push(
+            Row::new()
+                .push(
+                    Text::new(test.time.format("%Y-%m-%d %H:%M"))
+                        .size(14)
+                        .width(125),
+                )
+                .push(
+                    Text::new(format!("{:.0} net wpm", f64::from(test.net_wpm())))
+                        .size(14)
+                        .width(100),
+                )
+                .push(
+                    Text::new(format!("{:.0} gross wpm", f64::from(test.gross_wpm())))
+                        .size(14)
+                        .width(110),
+                )
+                .push(Text::new(format!("{:.1}%", 100.0 * test.accuracy())).size(14))
+                .spacing(10),
+        );
+    }
+
+    Column::new()
+        .push(headline)
+        .push(history)
+        .spacing(20)
+        .into()
+}
+
+fn stat<'a>(label: &str, value: String) -> Element<'a, Message> {
+    Column::new()
+        .push(Text::new(value).size(24))
+        .push(
+            Text::new(label.to_string())
+                .size(12)
+                .font(font::Font::Light),
+        )
+        .align_items(Alignment::Center)
+        .spacing(2)
+        .into()
+}
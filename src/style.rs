@@ -9,21 +9,79 @@ use iced::widget::{button, container, pick_list, rule, scrollable, text, text_in
 use iced::{Background, Color};
 
 use crate::data;
-use crate::data::theme::alpha;
 
 #[derive(Debug, Default, Clone)]
 pub struct Theme {
+    theme: Palette,
+}
+
+/// Colors of a theme, converted for iced
+#[derive(Debug, Clone)]
+pub struct Palette {
+    pub name: &'static str,
+    pub bg: Color,
+    pub text: Color,
+    pub target: Color,
+    pub hit: Color,
+    pub miss: Color,
+    pub error: Color,
     theme: data::Theme,
 }
 
 impl Theme {
     pub fn new(theme: data::Theme) -> Self {
-        Self { theme }
+        Self {
+            theme: theme.into(),
+        }
     }
 
     pub fn name(&self) -> &'static str {
         self.theme.name
     }
+
+    /// Colors of the active theme, for drawing on a canvas
+    pub fn palette(&self) -> &Palette {
+        &self.theme
+    }
+}
+
+impl Palette {
+    /// Between 0.0 and 0.5, return a blend from error -> text
+    /// Between 0.5 and 1.0, return a blend from text -> target
+    pub fn metric(&self, value: f32) -> Color {
+        self.theme.metric(value).into()
+    }
+}
+
+impl From<data::Theme> for Palette {
+    fn from(theme: data::Theme) -> Self {
+        Self {
+            name: theme.name,
+            bg: theme.bg.into(),
+            text: theme.text.into(),
+            target: theme.target.into(),
+            hit: theme.hit.into(),
+            miss: theme.miss.into(),
+            error: theme.error.into(),
+            theme,
+        }
+    }
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        data::Theme::default().into()
+    }
+}
+
+impl From<data::theme::Color> for Color {
+    fn from(color: data::theme::Color) -> Self {
+        Color::from_rgba(color.r, color.g, color.b, color.a)
+    }
+}
+
+pub fn alpha(color: Color, alpha: f32) -> Color {
+    Color { a: alpha, ..color }
 }
 
 impl iced::application::StyleSheet for Theme {
@@ -42,6 +100,8 @@ pub enum Text {
     #[default]
     Default,
     Error,
+    /// Cursor of the ghost being raced
+    Ghost,
     Metric(f32),
     Miss,
     Override(Color),
@@ -55,6 +115,7 @@ impl text::StyleSheet for Theme {
         let color = match style {
             Text::Default => None,
             Text::Error => Some(self.theme.error),
+            Text::Ghost => Some(alpha(self.theme.target, 0.5)),
             Text::Metric(metric) => Some(self.theme.metric(metric)),
             Text::Miss => Some(self.theme.miss),
             Text::Override(color) => Some(color),
@@ -79,7 +140,7 @@ pub enum Button {
     /// Bare text
     Text,
     /// Override theme
-    ThemePreview(data::Theme),
+    ThemePreview(Palette),
 }
 
 impl button::StyleSheet for Theme {
@@ -273,8 +334,8 @@ pub enum Container {
 impl Container {
     pub fn theme_preview(theme: &data::Theme) -> Self {
         Container::ThemePreview {
-            fg: theme.text,
-            bg: theme.bg.into(),
+            fg: theme.text.into(),
+            bg: Color::from(theme.bg).into(),
         }
     }
 }